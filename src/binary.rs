@@ -0,0 +1,214 @@
+//! Binary error bodies: MessagePack, CBOR and protobuf.
+//!
+//! Each encoding carries the same fields as the default JSON body, that is the
+//! [`ApiErrorResponse`] of the error. `meta` and the `params` of field errors are
+//! converted from `serde_json::Value` into the native maps, arrays and scalars of the
+//! encoding. The protobuf message is defined in `proto/axum_anyhow/v1/error.proto`.
+
+use crate::{ApiError, ApiErrorResponse};
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+#[cfg(feature = "msgpack")]
+const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+#[cfg(feature = "cbor")]
+const CBOR_CONTENT_TYPE: &str = "application/cbor";
+#[cfg(feature = "protobuf")]
+const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+
+/// Renders an `ApiError` as a MessagePack response, with the fields as a map.
+#[cfg(feature = "msgpack")]
+pub(crate) fn into_msgpack_response(error: ApiError) -> Response {
+    let status = error.status();
+    let body = rmp_serde::to_vec_named(&ApiErrorResponse::from(error))
+        .expect("ApiErrorResponse is always serializable");
+    response(status, body, MSGPACK_CONTENT_TYPE)
+}
+
+/// Renders an `ApiError` as a CBOR response.
+#[cfg(feature = "cbor")]
+pub(crate) fn into_cbor_response(error: ApiError) -> Response {
+    let status = error.status();
+    let mut body = Vec::new();
+    ciborium::into_writer(&ApiErrorResponse::from(error), &mut body)
+        .expect("ApiErrorResponse is always serializable");
+    response(status, body, CBOR_CONTENT_TYPE)
+}
+
+/// Renders an `ApiError` as an `axum_anyhow.v1.Error` protobuf response.
+#[cfg(feature = "protobuf")]
+pub(crate) fn into_protobuf_response(error: ApiError) -> Response {
+    let status = error.status();
+    let body = protobuf::encode_error(&ApiErrorResponse::from(error));
+    response(status, body, PROTOBUF_CONTENT_TYPE)
+}
+
+fn response(status: StatusCode, body: Vec<u8>, content_type: &'static str) -> Response {
+    let mut response = (status, body).into_response();
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    response
+}
+
+#[cfg(feature = "protobuf")]
+mod protobuf {
+    use crate::{
+        proto::{encode_bytes, encode_double, encode_string, encode_uint},
+        ApiErrorResponse, FieldError,
+    };
+    use serde_json::{Map, Value};
+
+    /// Encodes an `axum_anyhow.v1.Error` message.
+    pub(super) fn encode_error(body: &ApiErrorResponse) -> Vec<u8> {
+        let mut message = Vec::new();
+        encode_uint(&mut message, 1, body.status.into());
+        encode_string(&mut message, 2, &body.title);
+        if let Some(detail) = &body.detail {
+            encode_bytes(&mut message, 3, detail.as_bytes());
+        }
+        if let Some(meta) = &body.meta {
+            encode_bytes(&mut message, 4, &encode_value(meta));
+        }
+        for error in &body.errors {
+            encode_bytes(&mut message, 5, &encode_field_error(error));
+        }
+        message
+    }
+
+    /// Encodes an `axum_anyhow.v1.FieldError` message.
+    fn encode_field_error(error: &FieldError) -> Vec<u8> {
+        let mut message = Vec::new();
+        encode_string(&mut message, 1, &error.pointer);
+        encode_string(&mut message, 2, &error.message);
+        if let Some(code) = &error.code {
+            encode_bytes(&mut message, 3, code.as_bytes());
+        }
+        if let Some(params) = &error.params {
+            encode_bytes(&mut message, 4, &encode_struct(params));
+        }
+        message
+    }
+
+    /// Encodes a `google.protobuf.Value` message.
+    fn encode_value(value: &Value) -> Vec<u8> {
+        let mut message = Vec::new();
+        match value {
+            Value::Null => encode_uint(&mut message, 1, 0),
+            Value::Number(number) => {
+                encode_double(&mut message, 2, number.as_f64().unwrap_or_default());
+            }
+            Value::String(text) => encode_bytes(&mut message, 3, text.as_bytes()),
+            Value::Bool(flag) => encode_uint(&mut message, 4, u64::from(*flag)),
+            Value::Object(fields) => encode_bytes(&mut message, 5, &encode_struct(fields)),
+            Value::Array(items) => {
+                let mut list = Vec::new();
+                for item in items {
+                    encode_bytes(&mut list, 1, &encode_value(item));
+                }
+                encode_bytes(&mut message, 6, &list);
+            }
+        }
+        message
+    }
+
+    /// Encodes a `google.protobuf.Struct` message.
+    fn encode_struct(fields: &Map<String, Value>) -> Vec<u8> {
+        let mut message = Vec::new();
+        for (key, value) in fields {
+            let mut entry = Vec::new();
+            encode_string(&mut entry, 1, key);
+            encode_bytes(&mut entry, 2, &encode_value(value));
+            encode_bytes(&mut message, 1, &entry);
+        }
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorFormat, FieldError};
+    use http_body_util::BodyExt;
+    use serde_json::json;
+
+    #[cfg(any(feature = "msgpack", feature = "cbor"))]
+    fn error() -> ApiError {
+        ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("Invalid Order")
+            .detail("Quantity is required")
+            .meta(json!({"order": 7, "tags": ["rush"]}))
+            .field_error(FieldError::new("/quantity", "Required"))
+            .build()
+    }
+
+    async fn body(error: ApiError, format: ErrorFormat) -> (String, Vec<u8>) {
+        let response = error.into_response_as(format);
+        let content_type = response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (content_type, bytes.to_vec())
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[tokio::test]
+    async fn test_msgpack_round_trip() {
+        let (content_type, bytes) = body(error(), ErrorFormat::MessagePack).await;
+
+        assert_eq!(content_type, "application/msgpack");
+        let decoded: ApiErrorResponse = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, ApiErrorResponse::from(error()));
+    }
+
+    #[cfg(feature = "cbor")]
+    #[tokio::test]
+    async fn test_cbor_round_trip() {
+        let (content_type, bytes) = body(error(), ErrorFormat::Cbor).await;
+
+        assert_eq!(content_type, "application/cbor");
+        let decoded: ApiErrorResponse = ciborium::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(decoded, ApiErrorResponse::from(error()));
+    }
+
+    #[cfg(feature = "protobuf")]
+    #[tokio::test]
+    async fn test_protobuf_encoding() {
+        let error = ApiError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Gone")
+            .detail("")
+            .meta(json!({"id": 1, "ok": false, "none": null}))
+            .field_error(FieldError::new("id", "Unknown").code("x"))
+            .build();
+
+        let (content_type, bytes) = body(error, ErrorFormat::Protobuf).await;
+
+        assert_eq!(content_type, "application/x-protobuf");
+        let id = [&[0x11][..], &1f64.to_le_bytes()].concat();
+        let expected = [
+            // status = 404, title = "Gone", detail = ""
+            &[
+                0x08, 0x94, 0x03, 0x12, 0x04, b'G', b'o', b'n', b'e', 0x1a, 0x00,
+            ][..],
+            // meta = {"id": 1, "none": null, "ok": false}
+            &[0x22, 0x29, 0x2a, 0x27],
+            &[0x0a, 0x0f, 0x0a, 0x02, b'i', b'd', 0x12, 0x09],
+            &id,
+            &[
+                0x0a, 0x0a, 0x0a, 0x04, b'n', b'o', b'n', b'e', 0x12, 0x02, 0x08, 0x00,
+            ],
+            &[0x0a, 0x08, 0x0a, 0x02, b'o', b'k', 0x12, 0x02, 0x20, 0x00],
+            // errors = [{pointer: "id", message: "Unknown", code: "x"}]
+            &[0x2a, 0x10, 0x0a, 0x02, b'i', b'd', 0x12, 0x07],
+            b"Unknown",
+            &[0x1a, 0x01, b'x'],
+        ]
+        .concat();
+        assert_eq!(bytes, expected);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::{ApiError, ApiErrorBuilder};
+use axum::http::StatusCode;
+
+/// The canonical error codes shared by gRPC and Google-style APIs.
+///
+/// These codes are defined by [`google.rpc.Code`] and are used by gRPC, Connect and
+/// Google's API design guide. `axum-anyhow` maps HTTP status codes onto them when an
+/// `ApiError` is sent over one of those protocols.
+///
+/// [`google.rpc.Code`]: https://github.com/googleapis/googleapis/blob/master/google/rpc/code.proto
+///
+/// # Example
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum_anyhow::CanonicalCode;
+///
+/// let code = CanonicalCode::from_http(StatusCode::TOO_MANY_REQUESTS);
+/// assert_eq!(code, CanonicalCode::ResourceExhausted);
+/// assert_eq!(code.as_str(), "RESOURCE_EXHAUSTED");
+/// assert_eq!(code.to_http(), StatusCode::TOO_MANY_REQUESTS);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CanonicalCode {
+    /// Not an error
+    Ok,
+    /// The operation was cancelled, typically by the caller
+    Cancelled,
+    /// Unknown error
+    Unknown,
+    /// The client specified an invalid argument
+    InvalidArgument,
+    /// The deadline expired before the operation could complete
+    DeadlineExceeded,
+    /// Some requested entity was not found
+    NotFound,
+    /// The entity that a client attempted to create already exists
+    AlreadyExists,
+    /// The caller does not have permission to execute the operation
+    PermissionDenied,
+    /// Some resource has been exhausted, such as a quota or rate limit
+    ResourceExhausted,
+    /// The system is not in a state required for the operation's execution
+    FailedPrecondition,
+    /// The operation was aborted, typically due to a concurrency issue
+    Aborted,
+    /// The operation was attempted past the valid range
+    OutOfRange,
+    /// The operation is not implemented or not supported
+    Unimplemented,
+    /// Internal error
+    Internal,
+    /// The service is currently unavailable
+    Unavailable,
+    /// Unrecoverable data loss or corruption
+    DataLoss,
+    /// The request does not have valid authentication credentials
+    Unauthenticated,
+}
+
+impl CanonicalCode {
+    /// Maps an HTTP status code to the closest canonical code.
+    ///
+    /// | HTTP status   | Canonical code       |
+    /// | ------------- | -------------------- |
+    /// | 2xx           | `Ok`                 |
+    /// | 400, 422      | `InvalidArgument`    |
+    /// | 401           | `Unauthenticated`    |
+    /// | 403           | `PermissionDenied`   |
+    /// | 404           | `NotFound`           |
+    /// | 405, 501      | `Unimplemented`      |
+    /// | 408, 504      | `DeadlineExceeded`   |
+    /// | 409           | `AlreadyExists`      |
+    /// | 412           | `FailedPrecondition` |
+    /// | 416           | `OutOfRange`         |
+    /// | 429           | `ResourceExhausted`  |
+    /// | 499           | `Cancelled`          |
+    /// | 502, 503      | `Unavailable`        |
+    /// | other 4xx     | `FailedPrecondition` |
+    /// | other 5xx     | `Internal`           |
+    /// | anything else | `Unknown`            |
+    pub fn from_http(status: StatusCode) -> Self {
+        match status.as_u16() {
+            200..=299 => CanonicalCode::Ok,
+            400 | 422 => CanonicalCode::InvalidArgument,
+            401 => CanonicalCode::Unauthenticated,
+            403 => CanonicalCode::PermissionDenied,
+            404 => CanonicalCode::NotFound,
+            405 | 501 => CanonicalCode::Unimplemented,
+            408 | 504 => CanonicalCode::DeadlineExceeded,
+            409 => CanonicalCode::AlreadyExists,
+            412 => CanonicalCode::FailedPrecondition,
+            416 => CanonicalCode::OutOfRange,
+            429 => CanonicalCode::ResourceExhausted,
+            499 => CanonicalCode::Cancelled,
+            502 | 503 => CanonicalCode::Unavailable,
+            400..=499 => CanonicalCode::FailedPrecondition,
+            500..=599 => CanonicalCode::Internal,
+            _ => CanonicalCode::Unknown,
+        }
+    }
+
+    /// Maps this canonical code to its HTTP status code, as documented in
+    /// `google.rpc.Code`.
+    pub fn to_http(self) -> StatusCode {
+        match self {
+            CanonicalCode::Ok => StatusCode::OK,
+            CanonicalCode::Cancelled => {
+                StatusCode::from_u16(499).expect("499 is a valid status code")
+            }
+            CanonicalCode::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
+            CanonicalCode::InvalidArgument => StatusCode::BAD_REQUEST,
+            CanonicalCode::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+            CanonicalCode::NotFound => StatusCode::NOT_FOUND,
+            CanonicalCode::AlreadyExists => StatusCode::CONFLICT,
+            CanonicalCode::PermissionDenied => StatusCode::FORBIDDEN,
+            CanonicalCode::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+            CanonicalCode::FailedPrecondition => StatusCode::BAD_REQUEST,
+            CanonicalCode::Aborted => StatusCode::CONFLICT,
+            CanonicalCode::OutOfRange => StatusCode::BAD_REQUEST,
+            CanonicalCode::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+            CanonicalCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            CanonicalCode::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            CanonicalCode::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+            CanonicalCode::Unauthenticated => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    /// Returns the name of the code, e.g. `"NOT_FOUND"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CanonicalCode::Ok => "OK",
+            CanonicalCode::Cancelled => "CANCELLED",
+            CanonicalCode::Unknown => "UNKNOWN",
+            CanonicalCode::InvalidArgument => "INVALID_ARGUMENT",
+            CanonicalCode::DeadlineExceeded => "DEADLINE_EXCEEDED",
+            CanonicalCode::NotFound => "NOT_FOUND",
+            CanonicalCode::AlreadyExists => "ALREADY_EXISTS",
+            CanonicalCode::PermissionDenied => "PERMISSION_DENIED",
+            CanonicalCode::ResourceExhausted => "RESOURCE_EXHAUSTED",
+            CanonicalCode::FailedPrecondition => "FAILED_PRECONDITION",
+            CanonicalCode::Aborted => "ABORTED",
+            CanonicalCode::OutOfRange => "OUT_OF_RANGE",
+            CanonicalCode::Unimplemented => "UNIMPLEMENTED",
+            CanonicalCode::Internal => "INTERNAL",
+            CanonicalCode::Unavailable => "UNAVAILABLE",
+            CanonicalCode::DataLoss => "DATA_LOSS",
+            CanonicalCode::Unauthenticated => "UNAUTHENTICATED",
+        }
+    }
+}
+
+impl ApiErrorBuilder {
+    /// Sets the canonical code sent over gRPC, Connect and Twirp and in the Google
+    /// format, instead of the one mapped from the status code.
+    ///
+    /// Several canonical codes share an HTTP status code, so this is the only way to
+    /// send codes such as `Aborted`, which maps to 409 Conflict like `AlreadyExists`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::{ApiError, CanonicalCode};
+    ///
+    /// let error = ApiError::builder()
+    ///     .status(StatusCode::CONFLICT)
+    ///     .title("Transaction Aborted")
+    ///     .canonical_code(CanonicalCode::Aborted)
+    ///     .build();
+    ///
+    /// assert_eq!(error.canonical_code(), CanonicalCode::Aborted);
+    /// ```
+    pub fn canonical_code(mut self, code: CanonicalCode) -> Self {
+        self.extras_mut().canonical_code = Some(code);
+        self
+    }
+}
+
+impl ApiError {
+    /// Returns the canonical code of this error, which is the one set with
+    /// [`ApiErrorBuilder::canonical_code`] or else the one mapped from the status code.
+    pub fn canonical_code(&self) -> CanonicalCode {
+        self.extras()
+            .and_then(|extras| extras.canonical_code)
+            .unwrap_or_else(|| CanonicalCode::from_http(self.status()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_http_common_statuses() {
+        let cases = [
+            (StatusCode::BAD_REQUEST, CanonicalCode::InvalidArgument),
+            (StatusCode::UNAUTHORIZED, CanonicalCode::Unauthenticated),
+            (StatusCode::FORBIDDEN, CanonicalCode::PermissionDenied),
+            (StatusCode::NOT_FOUND, CanonicalCode::NotFound),
+            (StatusCode::CONFLICT, CanonicalCode::AlreadyExists),
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                CanonicalCode::ResourceExhausted,
+            ),
+            (StatusCode::INTERNAL_SERVER_ERROR, CanonicalCode::Internal),
+            (StatusCode::SERVICE_UNAVAILABLE, CanonicalCode::Unavailable),
+            (StatusCode::GATEWAY_TIMEOUT, CanonicalCode::DeadlineExceeded),
+            (StatusCode::IM_A_TEAPOT, CanonicalCode::FailedPrecondition),
+        ];
+
+        for (status, code) in cases {
+            assert_eq!(CanonicalCode::from_http(status), code, "{status}");
+        }
+    }
+
+    #[test]
+    fn test_to_http_round_trips_primary_codes() {
+        for status in [400, 401, 403, 404, 409, 429, 499, 500, 501, 503, 504] {
+            let status = StatusCode::from_u16(status).unwrap();
+            assert_eq!(CanonicalCode::from_http(status).to_http(), status);
+        }
+    }
+
+    #[test]
+    fn test_aborted_maps_to_conflict() {
+        assert_eq!(CanonicalCode::Aborted.to_http(), StatusCode::CONFLICT);
+        assert_eq!(CanonicalCode::Aborted.as_str(), "ABORTED");
+    }
+
+    #[test]
+    fn test_explicit_canonical_code() {
+        let conflict = crate::conflict("Conflict", "Email taken");
+        assert_eq!(conflict.canonical_code(), CanonicalCode::AlreadyExists);
+
+        let aborted = conflict
+            .into_builder()
+            .canonical_code(CanonicalCode::Aborted)
+            .build();
+        assert_eq!(aborted.status(), StatusCode::CONFLICT);
+        assert_eq!(aborted.canonical_code(), CanonicalCode::Aborted);
+    }
+}
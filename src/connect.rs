@@ -0,0 +1,468 @@
+//! [Connect](https://connectrpc.com/docs/protocol#error-end-stream) error format.
+//!
+//! Errors are rendered as a Connect error with a lowercase code. The metadata of the
+//! error is sent in a `google.rpc.ErrorInfo` detail, whose `value` is the base64-encoded
+//! protobuf message and whose `debug` field holds the same message as JSON:
+//!
+//! ```json
+//! {
+//!   "code": "not_found",
+//!   "message": "User Not Found: No user with ID 42",
+//!   "details": [
+//!     {
+//!       "type": "google.rpc.ErrorInfo",
+//!       "value": "Cg5VU0VSX05PVF9GT1VORBoNCgd1c2VyX2lkEgI0Mg",
+//!       "debug": { "reason": "USER_NOT_FOUND", "metadata": { "user_id": "42" } }
+//!     }
+//!   ]
+//! }
+//! ```
+
+use crate::{
+    google::metadata,
+    naming::reason,
+    proto::{decode_field, encode_bytes, encode_string},
+    ApiError, CanonicalCode, ErrorDetail,
+};
+use anyhow::anyhow;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+const ERROR_INFO_TYPE: &str = "google.rpc.ErrorInfo";
+
+impl ApiError {
+    /// Creates an `ApiError` from the status and body of a Connect error response.
+    ///
+    /// The Connect code is mapped to its HTTP status code, and a message of the form
+    /// `"title: detail"` is split back into title and detail. The metadata of a
+    /// `google.rpc.ErrorInfo` detail becomes the error metadata. If the body is not a
+    /// Connect error, the canonical reason of the status code is used as the title.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::ApiError;
+    ///
+    /// let body = br#"{"code": "already_exists", "message": "Conflict: Email taken"}"#;
+    /// let error = ApiError::from_connect(StatusCode::CONFLICT, body);
+    ///
+    /// assert_eq!(error.status(), StatusCode::CONFLICT);
+    /// assert_eq!(error.title(), "Conflict");
+    /// assert_eq!(error.detail(), Some("Email taken"));
+    /// ```
+    pub fn from_connect(status: StatusCode, body: &[u8]) -> ApiError {
+        let body = match serde_json::from_slice::<Value>(body) {
+            Ok(body) if body["code"].is_string() => body,
+            Ok(_) => return unrecognized(status, anyhow!("missing Connect error code")),
+            Err(err) => return unrecognized(status, anyhow!(err)),
+        };
+        let name = body["code"].as_str().unwrap_or_default();
+        let code = from_name(name);
+        let message = body["message"].as_str().unwrap_or_default();
+
+        let mut builder = ApiError::builder().status(code.map_or(status, CanonicalCode::to_http));
+        if let Some(code) = code.filter(|code| CanonicalCode::from_http(code.to_http()) != *code) {
+            builder = builder.canonical_code(code);
+        }
+        builder = match message.split_once(": ") {
+            Some((title, detail)) => builder.title(title).detail(detail),
+            None if message.is_empty() => match code {
+                Some(code) => builder.title(code.as_str()),
+                None => builder.title(name),
+            },
+            None => builder.title(message),
+        };
+
+        let info = body["details"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|detail| {
+                detail["type"].as_str().is_some_and(|name| {
+                    name.trim_start_matches("type.googleapis.com/") == ERROR_INFO_TYPE
+                })
+            })
+            .and_then(error_info_metadata);
+        if let Some(meta) = info.filter(|meta| !meta.is_empty()) {
+            let meta = meta
+                .into_iter()
+                .map(|(key, value)| (key, Value::String(value)));
+            builder = builder.meta(Value::Object(meta.collect()));
+        }
+
+        builder
+            .error(anyhow!("Connect error {name}: {message}"))
+            .build()
+    }
+}
+
+/// Renders an `ApiError` as a Connect error response.
+///
+/// The HTTP status code is the one that the Connect protocol assigns to the code in the
+/// body, so a 504 is sent as `deadline_exceeded` with status 504, and a 2xx error as
+/// `unknown` with status 500.
+pub(crate) fn into_response(error: ApiError) -> Response {
+    let status = code(&error).to_http();
+    (status, Json(to_json(&error))).into_response()
+}
+
+/// Returns the canonical code sent for an error, which is never `Ok`.
+fn code(error: &ApiError) -> CanonicalCode {
+    match error.canonical_code() {
+        CanonicalCode::Ok => CanonicalCode::Unknown,
+        code => code,
+    }
+}
+
+/// Builds the Connect error document.
+pub(crate) fn to_json(error: &ApiError) -> Value {
+    let mut body = Map::new();
+    body.insert("code".to_string(), json!(name(code(error))));
+    body.insert("message".to_string(), json!(error.message()));
+
+    let metadata = error.meta().map(metadata).unwrap_or_default();
+    let info = error.details().iter().find_map(|detail| match detail {
+        ErrorDetail::ErrorInfo(info) => Some((info.reason.clone(), info.domain.clone())),
+        _ => None,
+    });
+    let info = info.or_else(|| {
+        (!metadata.is_empty()).then(|| {
+            let reason = error.code().map_or_else(
+                || reason(error.title(), error.canonical_code()),
+                str::to_string,
+            );
+            (reason, String::new())
+        })
+    });
+    if let Some((reason, domain)) = info {
+        let mut debug = json!({ "reason": reason });
+        if !domain.is_empty() {
+            debug["domain"] = json!(domain);
+        }
+        if !metadata.is_empty() {
+            debug["metadata"] = json!(metadata);
+        }
+        let value = base64_encode(&encode_error_info(&reason, &domain, &metadata));
+        body.insert(
+            "details".to_string(),
+            json!([{ "type": ERROR_INFO_TYPE, "value": value, "debug": debug }]),
+        );
+    }
+
+    Value::Object(body)
+}
+
+/// Returns the Connect name of a canonical code, such as `"not_found"`.
+fn name(code: CanonicalCode) -> &'static str {
+    match code {
+        CanonicalCode::Ok | CanonicalCode::Unknown => "unknown",
+        CanonicalCode::Cancelled => "canceled",
+        CanonicalCode::InvalidArgument => "invalid_argument",
+        CanonicalCode::DeadlineExceeded => "deadline_exceeded",
+        CanonicalCode::NotFound => "not_found",
+        CanonicalCode::AlreadyExists => "already_exists",
+        CanonicalCode::PermissionDenied => "permission_denied",
+        CanonicalCode::ResourceExhausted => "resource_exhausted",
+        CanonicalCode::FailedPrecondition => "failed_precondition",
+        CanonicalCode::Aborted => "aborted",
+        CanonicalCode::OutOfRange => "out_of_range",
+        CanonicalCode::Unimplemented => "unimplemented",
+        CanonicalCode::Internal => "internal",
+        CanonicalCode::Unavailable => "unavailable",
+        CanonicalCode::DataLoss => "data_loss",
+        CanonicalCode::Unauthenticated => "unauthenticated",
+    }
+}
+
+/// Parses the Connect name of a canonical code.
+fn from_name(name: &str) -> Option<CanonicalCode> {
+    let code = match name {
+        "canceled" => CanonicalCode::Cancelled,
+        "unknown" => CanonicalCode::Unknown,
+        "invalid_argument" => CanonicalCode::InvalidArgument,
+        "deadline_exceeded" => CanonicalCode::DeadlineExceeded,
+        "not_found" => CanonicalCode::NotFound,
+        "already_exists" => CanonicalCode::AlreadyExists,
+        "permission_denied" => CanonicalCode::PermissionDenied,
+        "resource_exhausted" => CanonicalCode::ResourceExhausted,
+        "failed_precondition" => CanonicalCode::FailedPrecondition,
+        "aborted" => CanonicalCode::Aborted,
+        "out_of_range" => CanonicalCode::OutOfRange,
+        "unimplemented" => CanonicalCode::Unimplemented,
+        "internal" => CanonicalCode::Internal,
+        "unavailable" => CanonicalCode::Unavailable,
+        "data_loss" => CanonicalCode::DataLoss,
+        "unauthenticated" => CanonicalCode::Unauthenticated,
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn unrecognized(status: StatusCode, err: anyhow::Error) -> ApiError {
+    ApiError::builder()
+        .status(status)
+        .title(status.canonical_reason().unwrap_or("Unknown"))
+        .error(err.context(format!(
+            "Connect peer returned status {status} with an unrecognized error body"
+        )))
+        .build()
+}
+
+/// Reads the metadata of an `ErrorInfo` detail, from its `debug` JSON if present and
+/// from the protobuf `value` otherwise.
+fn error_info_metadata(detail: &Value) -> Option<BTreeMap<String, String>> {
+    if let Some(metadata) = detail["debug"]["metadata"].as_object() {
+        return Some(
+            metadata
+                .iter()
+                .map(|(key, value)| match value {
+                    Value::String(value) => (key.clone(), value.clone()),
+                    value => (key.clone(), value.to_string()),
+                })
+                .collect(),
+        );
+    }
+    let value = base64_decode(detail["value"].as_str()?)?;
+    decode_error_info_metadata(&value)
+}
+
+/// Encodes a `google.rpc.ErrorInfo` protobuf message.
+fn encode_error_info(reason: &str, domain: &str, metadata: &BTreeMap<String, String>) -> Vec<u8> {
+    let mut message = Vec::new();
+    encode_string(&mut message, 1, reason);
+    encode_string(&mut message, 2, domain);
+    for (key, value) in metadata {
+        let mut entry = Vec::new();
+        encode_string(&mut entry, 1, key);
+        encode_string(&mut entry, 2, value);
+        encode_bytes(&mut message, 3, &entry);
+    }
+    message
+}
+
+/// Decodes the `metadata` map of a `google.rpc.ErrorInfo` protobuf message.
+fn decode_error_info_metadata(mut message: &[u8]) -> Option<BTreeMap<String, String>> {
+    let mut metadata = BTreeMap::new();
+    while !message.is_empty() {
+        let (field, bytes) = decode_field(&mut message)?;
+        if field != 3 {
+            continue;
+        }
+        let mut entry = bytes?;
+        let (mut key, mut value) = (String::new(), String::new());
+        while !entry.is_empty() {
+            match decode_field(&mut entry)? {
+                (1, Some(bytes)) => key = String::from_utf8(bytes.to_vec()).ok()?,
+                (2, Some(bytes)) => value = String::from_utf8(bytes.to_vec()).ok()?,
+                _ => {}
+            }
+        }
+        metadata.insert(key, value);
+    }
+    Some(metadata)
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as unpadded standard base64, as Connect does for detail values.
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            encoded.push(BASE64_ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    encoded
+}
+
+/// Decodes standard or URL-safe base64, with or without padding.
+///
+/// Input with an impossible length, misplaced or excess padding, or non-zero bits after
+/// the last byte is rejected.
+fn base64_decode(encoded: &str) -> Option<Vec<u8>> {
+    let data = encoded.trim_end_matches('=');
+    let padding = encoded.len() - data.len();
+    if data.len() % 4 == 1 || padding > 2 || (padding > 0 && !encoded.len().is_multiple_of(4)) {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    let (mut group, mut bits) = (0u32, 0);
+    for c in data.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        group = group << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((group >> bits) as u8);
+        }
+    }
+    (group & ((1 << bits) - 1) == 0).then_some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorFormat;
+    use http_body_util::BodyExt;
+
+    async fn body(error: ApiError) -> Value {
+        let response = error.into_response_as(ErrorFormat::Connect);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_connect_error_with_metadata() {
+        let error = ApiError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("User Not Found")
+            .detail("No user with ID 42")
+            .meta(json!({"user_id": 42}))
+            .build();
+
+        assert_eq!(
+            body(error).await,
+            json!({
+                "code": "not_found",
+                "message": "User Not Found: No user with ID 42",
+                "details": [{
+                    "type": "google.rpc.ErrorInfo",
+                    "value": "Cg5VU0VSX05PVF9GT1VORBoNCgd1c2VyX2lkEgI0Mg",
+                    "debug": {"reason": "USER_NOT_FOUND", "metadata": {"user_id": "42"}},
+                }],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connect_codes() {
+        let code = |status: u16| async move {
+            let error = ApiError::builder()
+                .status(StatusCode::from_u16(status).unwrap())
+                .build();
+            body(error).await["code"].clone()
+        };
+
+        assert_eq!(code(499).await, "canceled");
+        assert_eq!(code(401).await, "unauthenticated");
+        assert_eq!(code(429).await, "resource_exhausted");
+        assert_eq!(code(500).await, "internal");
+        assert_eq!(code(503).await, "unavailable");
+    }
+
+    #[tokio::test]
+    async fn test_connect_http_status_follows_code() {
+        let response = ApiError::builder()
+            .status(StatusCode::ACCEPTED)
+            .title("Queued")
+            .build()
+            .into_response_as(ErrorFormat::Connect);
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["code"], "unknown");
+
+        let response = ApiError::builder()
+            .status(StatusCode::GATEWAY_TIMEOUT)
+            .build()
+            .into_response_as(ErrorFormat::Connect);
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let response = ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .build()
+            .into_response_as(ErrorFormat::Connect);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_from_connect_decodes_error_info_value() {
+        let body = json!({
+            "code": "resource_exhausted",
+            "message": "Quota Exceeded",
+            "details": [{
+                "type": "type.googleapis.com/google.rpc.ErrorInfo",
+                "value": base64_encode(&encode_error_info(
+                    "QUOTA",
+                    "api.example.com",
+                    &BTreeMap::from([("limit".to_string(), "100".to_string())]),
+                )),
+            }],
+        });
+
+        let error =
+            ApiError::from_connect(StatusCode::TOO_MANY_REQUESTS, body.to_string().as_bytes());
+
+        assert_eq!(error.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(error.title(), "Quota Exceeded");
+        assert_eq!(error.meta(), Some(&json!({"limit": "100"})));
+        assert_eq!(
+            error.error().unwrap().to_string(),
+            "Connect error resource_exhausted: Quota Exceeded"
+        );
+    }
+
+    #[test]
+    fn test_from_connect_round_trips() {
+        let error = ApiError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("Order Locked")
+            .detail("Try again later")
+            .meta(json!({"order": "7"}))
+            .build();
+
+        let body = to_json(&error).to_string();
+        let error = ApiError::from_connect(StatusCode::INTERNAL_SERVER_ERROR, body.as_bytes());
+
+        assert_eq!(error.status(), StatusCode::CONFLICT);
+        assert_eq!(error.title(), "Order Locked");
+        assert_eq!(error.detail(), Some("Try again later"));
+        assert_eq!(error.meta(), Some(&json!({"order": "7"})));
+    }
+
+    #[test]
+    fn test_from_connect_unrecognized_body() {
+        let error = ApiError::from_connect(StatusCode::BAD_GATEWAY, b"<html>");
+
+        assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(error.title(), "Bad Gateway");
+    }
+
+    #[test]
+    fn test_base64_round_trip() {
+        for input in [&b""[..], b"f", b"fo", b"foo", b"foob", b"\xff\xfe\x00"] {
+            assert_eq!(base64_decode(&base64_encode(input)).unwrap(), input);
+        }
+        assert_eq!(base64_encode(b"foob"), "Zm9vYg");
+        assert_eq!(base64_decode("Zm9vYg==").unwrap(), b"foob");
+        assert_eq!(base64_decode("-_8").unwrap(), b"\xfb\xff");
+    }
+
+    #[test]
+    fn test_base64_rejects_invalid_input() {
+        assert_eq!(base64_decode("Zm9vY"), None);
+        assert_eq!(base64_decode("Zm9vYg="), None);
+        assert_eq!(base64_decode("Zm9v===="), None);
+        assert_eq!(base64_decode("Zm=9vYg"), None);
+        assert_eq!(base64_decode("Zm9vYh"), None);
+        assert_eq!(base64_decode("Zm9v Yg"), None);
+    }
+}
@@ -1,4 +1,9 @@
-/// Context for an API error: a title and an optional detail.
+use crate::ApiErrorBuilder;
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Context for an API error: a title, an optional detail, and optional extra fields.
 ///
 /// Methods on [`ResultExt`](crate::ResultExt), [`OptionExt`](crate::OptionExt), and
 /// [`IntoApiError`](crate::IntoApiError) accept `impl Into<Context>`, so you can pass
@@ -20,61 +25,215 @@
 /// // title + detail
 /// let _: ApiResult<()> = fallible().context_not_found(("Not Found", "The resource does not exist"));
 /// ```
+///
+/// To attach a code, metadata or headers as well, build the context with [`ctx`]:
+///
+/// ```rust
+/// use axum_anyhow::{ctx, ApiResult, ResultExt};
+/// use axum::http::{header, HeaderValue};
+/// use anyhow::anyhow;
+/// use serde_json::json;
+///
+/// fn reserve() -> anyhow::Result<()> {
+///     Err(anyhow!("version mismatch"))
+/// }
+///
+/// let err = reserve()
+///     .context_conflict(
+///         ctx("Conflict")
+///             .detail("The seat was reserved by someone else")
+///             .code("SEAT_TAKEN")
+///             .meta(json!({"seat": "12A"}))
+///             .header(header::CACHE_CONTROL, HeaderValue::from_static("no-store")),
+///     )
+///     .unwrap_err();
+///
+/// assert_eq!(err.code(), Some("SEAT_TAKEN"));
+/// assert_eq!(err.meta().unwrap()["seat"], "12A");
+/// assert_eq!(err.headers().unwrap()[header::CACHE_CONTROL], "no-store");
+/// ```
+#[derive(Clone, Debug)]
 pub struct ApiErrorContext {
-    pub(crate) title: String,
-    pub(crate) detail: Option<String>,
+    title: String,
+    detail: Option<String>,
+    code: Option<String>,
+    meta: Option<Value>,
+    headers: HeaderMap,
 }
 
-impl From<&str> for ApiErrorContext {
-    fn from(title: &str) -> Self {
+/// Creates an [`ApiErrorContext`] with the given title.
+///
+/// This is a shorthand for [`ApiErrorContext::new`].
+pub fn ctx(title: impl Into<String>) -> ApiErrorContext {
+    ApiErrorContext::new(title)
+}
+
+impl ApiErrorContext {
+    /// Creates a context with the given title.
+    pub fn new(title: impl Into<String>) -> Self {
         Self {
-            title: title.to_string(),
+            title: title.into(),
             detail: None,
+            code: None,
+            meta: None,
+            headers: HeaderMap::new(),
         }
     }
+
+    /// Sets the detail of the error.
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the application-specific error code.
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Sets the metadata of the error.
+    ///
+    /// If the error already has object metadata, such as the pool statistics added by
+    /// `context_pool_stats`, the two objects are merged.
+    pub fn meta(mut self, meta: Value) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+
+    /// Adds a header to the error response.
+    ///
+    /// Headers set by the context replace headers of the same name set by the method
+    /// that applies it.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Sets the `Retry-After` header to the given delay, rounded up to whole seconds.
+    pub fn retry_after(mut self, delay: Duration) -> Self {
+        let secs = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+        self.headers
+            .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        self
+    }
+
+    /// Applies the context to an error builder.
+    pub(crate) fn apply(self, mut builder: ApiErrorBuilder) -> ApiErrorBuilder {
+        builder = builder.title(self.title);
+        if let Some(detail) = self.detail {
+            builder = builder.detail(detail);
+        }
+        if let Some(code) = self.code {
+            builder = builder.code(code);
+        }
+        if let Some(meta) = self.meta {
+            let meta = match (builder.get_meta(), meta) {
+                (Some(Value::Object(existing)), Value::Object(meta)) => {
+                    let mut merged = existing.clone();
+                    merged.extend(meta);
+                    Value::Object(merged)
+                }
+                (_, meta) => meta,
+            };
+            builder = builder.meta(meta);
+        }
+        // Replaces headers of the same name, such as a default `Retry-After`
+        builder.extras_mut().headers.extend(self.headers);
+        builder
+    }
+}
+
+impl From<&str> for ApiErrorContext {
+    fn from(title: &str) -> Self {
+        Self::new(title)
+    }
 }
 
 impl From<String> for ApiErrorContext {
     fn from(title: String) -> Self {
-        Self {
-            title,
-            detail: None,
-        }
+        Self::new(title)
     }
 }
 
 impl From<(&str, &str)> for ApiErrorContext {
     fn from((title, detail): (&str, &str)) -> Self {
-        Self {
-            title: title.to_string(),
-            detail: Some(detail.to_string()),
-        }
+        Self::new(title).detail(detail)
     }
 }
 
 impl From<(String, String)> for ApiErrorContext {
     fn from((title, detail): (String, String)) -> Self {
-        Self {
-            title,
-            detail: Some(detail),
-        }
+        Self::new(title).detail(detail)
     }
 }
 
 impl From<(&str, String)> for ApiErrorContext {
     fn from((title, detail): (&str, String)) -> Self {
-        Self {
-            title: title.to_string(),
-            detail: Some(detail),
-        }
+        Self::new(title).detail(detail)
     }
 }
 
 impl From<(String, &str)> for ApiErrorContext {
     fn from((title, detail): (String, &str)) -> Self {
-        Self {
-            title,
-            detail: Some(detail.to_string()),
-        }
+        Self::new(title).detail(detail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IntoApiError, OptionExt};
+    use anyhow::anyhow;
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    #[test]
+    fn test_tuple_context_sets_title_and_detail() {
+        let error = None::<()>
+            .context_not_found(("Not Found", "No such user"))
+            .unwrap_err();
+
+        assert_eq!(error.title(), "Not Found");
+        assert_eq!(error.detail(), Some("No such user"));
+        assert_eq!(error.code(), None);
+        assert!(error.headers().is_none());
+    }
+
+    #[test]
+    fn test_builder_context_applies_every_field() {
+        let error = anyhow!("rate limited").context_too_many_requests(
+            ctx("Slow Down")
+                .code("RATE_LIMITED")
+                .meta(json!({"limit": 10}))
+                .retry_after(Duration::from_millis(1500)),
+        );
+
+        assert_eq!(error.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(error.title(), "Slow Down");
+        assert_eq!(error.code(), Some("RATE_LIMITED"));
+        assert_eq!(error.meta(), Some(&json!({"limit": 10})));
+        assert_eq!(error.headers().unwrap()[header::RETRY_AFTER], "2");
+    }
+
+    #[test]
+    fn test_apply_merges_meta_and_replaces_headers() {
+        let builder = crate::ApiError::builder()
+            .meta(json!({"pool": "db", "size": 1}))
+            .retry_after(Duration::from_secs(5));
+        let error = ctx("Unavailable")
+            .meta(json!({"size": 2}))
+            .retry_after(Duration::from_secs(30))
+            .apply(builder)
+            .build();
+
+        assert_eq!(error.meta(), Some(&json!({"pool": "db", "size": 2})));
+        let retry_after: Vec<_> = error
+            .headers()
+            .unwrap()
+            .get_all(header::RETRY_AFTER)
+            .iter()
+            .collect();
+        assert_eq!(retry_after, ["30"]);
     }
 }
@@ -1,13 +1,22 @@
-use crate::{hook::invoke_hook, middleware::EnrichmentContext};
+use crate::{
+    format::{error_format, field_error_format},
+    hook::invoke_hook,
+    middleware::EnrichmentContext,
+    CanonicalCode, ErrorDetail, ErrorFormat, ErrorSource, FieldError, FieldErrorFormat,
+    PropagationPolicy, ScimType,
+};
 use anyhow::Error;
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
 /// Global flag to control whether error details should be exposed in API responses.
 /// This can be set programmatically or via the `AXUM_ANYHOW_EXPOSE_ERRORS` environment variable.
@@ -103,6 +112,8 @@ pub struct ApiError {
     detail: Option<String>,
     /// Optional metadata that can be included in the error response
     meta: Option<Value>,
+    /// Optional parts that most errors leave empty, boxed to keep `ApiError` small
+    extras: Option<Box<Extras>>,
     /// The underlying error that caused this API error
     error: Option<Error>,
 }
@@ -128,11 +139,48 @@ impl ApiError {
         self.meta.as_ref()
     }
 
+    /// Gets the additional response headers, if any
+    pub fn headers(&self) -> Option<&HeaderMap> {
+        self.extras
+            .as_ref()
+            .map(|extras| &extras.headers)
+            .filter(|headers| !headers.is_empty())
+    }
+
+    /// Gets the typed error details, such as [`ErrorInfo`](crate::ErrorInfo)
+    pub fn details(&self) -> &[ErrorDetail] {
+        self.extras
+            .as_ref()
+            .map_or(&[], |extras| extras.details.as_slice())
+    }
+
+    /// Gets the errors of individual request fields
+    pub fn field_errors(&self) -> &[FieldError] {
+        self.extras
+            .as_ref()
+            .map_or(&[], |extras| extras.field_errors.as_slice())
+    }
+
+    /// Gets the application-specific error code, if any
+    pub fn code(&self) -> Option<&str> {
+        self.extras.as_ref()?.code.as_deref()
+    }
+
+    /// Gets the part of the request that caused the error, if known
+    pub fn source(&self) -> Option<&ErrorSource> {
+        self.extras.as_ref()?.source.as_ref()
+    }
+
     /// Gets the underlying error, if any
     pub fn error(&self) -> Option<&Error> {
         self.error.as_ref()
     }
 
+    /// Returns the optional parts of the error, for getters defined in other modules.
+    pub(crate) fn extras(&self) -> Option<&Extras> {
+        self.extras.as_deref()
+    }
+
     /// Creates a new builder for constructing an `ApiError`.
     ///
     /// # Example
@@ -151,6 +199,19 @@ impl ApiError {
     pub fn builder() -> ApiErrorBuilder {
         ApiErrorBuilder::default()
     }
+
+    /// Converts the error back into a builder that keeps every field.
+    pub(crate) fn into_builder(self) -> ApiErrorBuilder {
+        ApiErrorBuilder {
+            status: Some(self.status),
+            title: Some(self.title),
+            detail: self.detail,
+            meta: self.meta,
+            extras: self.extras.map(|extras| *extras).unwrap_or_default(),
+            error: self.error,
+        }
+    }
+
     /// Converts this `ApiError` into an `anyhow::Error`.
     ///
     /// If the `ApiError` contains an underlying error, it will be returned with
@@ -174,16 +235,37 @@ impl ApiError {
     /// let anyhow_error = api_error.into_error();
     /// ```
     pub fn into_error(self) -> Error {
-        let msg = match self.detail {
-            Some(detail) => format!("{}: {}", self.title, detail),
-            None => self.title.clone(),
-        };
+        let msg = self.message();
         if let Some(error) = self.error {
             error.context(msg)
         } else {
             anyhow::anyhow!("{}", msg)
         }
     }
+
+    /// Creates an `ApiError` from the status and body of an upstream error response.
+    ///
+    /// This decodes error bodies produced by another service built with `axum-anyhow`,
+    /// keeping the upstream status, title, detail and metadata. If the body cannot be
+    /// decoded, the canonical reason of the status code is used as the title. To wrap
+    /// or rewrite upstream errors instead, see [`PropagationPolicy`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::ApiError;
+    ///
+    /// let body = br#"{"status": 404, "title": "Not Found", "detail": "No such user"}"#;
+    /// let error = ApiError::from_response(StatusCode::NOT_FOUND, body);
+    ///
+    /// assert_eq!(error.status(), StatusCode::NOT_FOUND);
+    /// assert_eq!(error.title(), "Not Found");
+    /// assert_eq!(error.detail(), Some("No such user"));
+    /// ```
+    pub fn from_response(status: StatusCode, body: &[u8]) -> ApiError {
+        PropagationPolicy::PassThrough.apply(status, body)
+    }
 }
 
 impl Default for ApiError {
@@ -211,6 +293,7 @@ impl Default for ApiError {
             title: "Internal Error".to_string(),
             detail: None,
             meta: None,
+            extras: None,
             error: None,
         }
     }
@@ -240,33 +323,284 @@ where
 }
 
 /// The JSON structure used in error responses.
-#[derive(Serialize)]
-struct ApiErrorResponse {
-    status: u16,
-    title: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    detail: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    meta: Option<Value>,
+///
+/// This type is public so that services calling each other can decode an error body
+/// produced by `axum-anyhow` back into its fields. See [`ApiError::from_response`] to
+/// turn an upstream error response directly into an `ApiError`.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::ApiErrorResponse;
+///
+/// let body = br#"{"status": 404, "title": "Not Found", "detail": "No such user"}"#;
+/// let response: ApiErrorResponse = serde_json::from_slice(body).unwrap();
+///
+/// assert_eq!(response.status, 404);
+/// assert_eq!(response.title, "Not Found");
+/// assert_eq!(response.detail.as_deref(), Some("No such user"));
+/// assert!(response.meta.is_none());
+/// ```
+///
+/// New fields may be added in future releases, so values are created with
+/// [`ApiErrorResponse::new`] rather than a struct literal.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ApiErrorResponse {
+    /// The HTTP status code
+    pub status: u16,
+    /// A short, human-readable summary of the error
+    pub title: String,
+    /// A detailed explanation of the error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Optional metadata
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
+    /// Errors of individual request fields, also read from `invalid-params`
+    #[serde(
+        default,
+        alias = "invalid-params",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub errors: Vec<FieldError>,
+}
+
+impl ApiErrorResponse {
+    /// Creates a response body with the given status code and title, and no other fields.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_anyhow::ApiErrorResponse;
+    ///
+    /// let mut response = ApiErrorResponse::new(404, "Not Found");
+    /// response.detail = Some("No such user".to_string());
+    ///
+    /// assert_eq!(response.status, 404);
+    /// assert!(response.errors.is_empty());
+    /// ```
+    pub fn new(status: u16, title: impl Into<String>) -> Self {
+        ApiErrorResponse {
+            status,
+            title: title.into(),
+            detail: None,
+            meta: None,
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// Converts an `ApiError` into the fields of its default JSON body.
+///
+/// This is useful where an error has to be written somewhere other than a response body,
+/// such as a record at the end of a stream.
+impl From<ApiError> for ApiErrorResponse {
+    fn from(error: ApiError) -> Self {
+        ApiErrorResponse {
+            status: error.status.as_u16(),
+            title: error.title,
+            detail: error.detail,
+            meta: error.meta,
+            errors: error
+                .extras
+                .map(|extras| extras.field_errors)
+                .unwrap_or_default(),
+        }
+    }
 }
 
 /// Converts from `ApiError` to an HTTP `Response`.
 ///
 /// This implementation allows `ApiError` to be used as a return type in Axum handlers.
-/// The error is serialized as JSON with the status code, title, and detail fields.
+/// The body is rendered in the current [`ErrorFormat`], which by default is JSON with
+/// the status code, title, and detail fields.
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let body = Json(ApiErrorResponse {
-            status: self.status.as_u16(),
-            title: self.title,
-            detail: self.detail,
-            meta: self.meta,
-        });
+        self.into_response_as(error_format())
+    }
+}
 
-        (self.status, body).into_response()
+impl ApiError {
+    /// Converts this error into a response whose body is rendered in the given format,
+    /// regardless of the format set with [`set_error_format`](crate::set_error_format).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_anyhow::{not_found, ErrorFormat};
+    ///
+    /// let response = not_found("Not Found", "No such user").into_response_as(ErrorFormat::Google);
+    /// assert_eq!(response.status(), 404);
+    /// ```
+    pub fn into_response_as(mut self, format: ErrorFormat) -> Response {
+        let headers = self
+            .extras
+            .as_mut()
+            .map(|extras| std::mem::take(&mut extras.headers))
+            .unwrap_or_default();
+
+        let mut response = match format {
+            ErrorFormat::Problem => self.into_problem_response(),
+            ErrorFormat::Google => crate::google::into_response(self),
+            ErrorFormat::JsonApi => crate::jsonapi::into_response(self),
+            ErrorFormat::JsonRpc => crate::jsonrpc::into_response(self),
+            ErrorFormat::Connect => crate::connect::into_response(self),
+            ErrorFormat::Twirp => crate::twirp::into_response(self),
+            ErrorFormat::Spring => crate::presets::into_spring_response(self),
+            ErrorFormat::FastApi => crate::presets::into_fastapi_response(self),
+            ErrorFormat::Drf => crate::presets::into_drf_response(self),
+            ErrorFormat::Scim => crate::scim::into_response(self),
+            ErrorFormat::OData => crate::odata::into_response(self),
+            #[cfg(feature = "xml")]
+            ErrorFormat::ProblemXml => crate::xml::into_problem_response(self),
+            #[cfg(feature = "xml")]
+            ErrorFormat::S3 => crate::xml::into_s3_response(self),
+            #[cfg(feature = "msgpack")]
+            ErrorFormat::MessagePack => crate::binary::into_msgpack_response(self),
+            #[cfg(feature = "cbor")]
+            ErrorFormat::Cbor => crate::binary::into_cbor_response(self),
+            #[cfg(feature = "protobuf")]
+            ErrorFormat::Protobuf => crate::binary::into_protobuf_response(self),
+        };
+        response.headers_mut().extend(headers);
+        response
+    }
+
+    /// Renders the default JSON body.
+    fn into_problem_response(self) -> Response {
+        let status = self.status;
+        let body = ApiErrorResponse::from(self);
+
+        match field_error_format() {
+            FieldErrorFormat::InvalidParams if !body.errors.is_empty() => {
+                (status, Json(invalid_params(body))).into_response()
+            }
+            _ => (status, Json(body)).into_response(),
+        }
     }
+
+    /// Renders the body of the error in the given format, for transports other than an
+    /// HTTP response.
+    ///
+    /// The XML and binary formats have no JSON form, so they are rendered as the default
+    /// body.
+    pub(crate) fn into_json(self, format: ErrorFormat) -> Value {
+        match format {
+            #[cfg(feature = "xml")]
+            ErrorFormat::ProblemXml | ErrorFormat::S3 => self.into_json(ErrorFormat::Problem),
+            #[cfg(feature = "msgpack")]
+            ErrorFormat::MessagePack => self.into_json(ErrorFormat::Problem),
+            #[cfg(feature = "cbor")]
+            ErrorFormat::Cbor => self.into_json(ErrorFormat::Problem),
+            #[cfg(feature = "protobuf")]
+            ErrorFormat::Protobuf => self.into_json(ErrorFormat::Problem),
+            ErrorFormat::Problem => {
+                let body = ApiErrorResponse::from(self);
+                match field_error_format() {
+                    FieldErrorFormat::InvalidParams if !body.errors.is_empty() => {
+                        invalid_params(body)
+                    }
+                    _ => {
+                        serde_json::to_value(body).expect("ApiErrorResponse is always serializable")
+                    }
+                }
+            }
+            ErrorFormat::Google => crate::google::to_json(&self),
+            ErrorFormat::JsonApi => crate::jsonapi::to_json(&self),
+            ErrorFormat::JsonRpc => crate::jsonrpc::to_json(&self),
+            ErrorFormat::Connect => crate::connect::to_json(&self),
+            ErrorFormat::Twirp => crate::twirp::to_json(&self),
+            ErrorFormat::Spring => crate::presets::spring_json(&self),
+            ErrorFormat::FastApi => crate::presets::fastapi_json(&self),
+            ErrorFormat::Drf => crate::presets::drf_json(&self),
+            ErrorFormat::Scim => crate::scim::to_json(&self),
+            ErrorFormat::OData => crate::odata::to_json(&self),
+        }
+    }
+
+    /// Combines the title and detail into a single message, as used by protocols that
+    /// only carry one string.
+    pub(crate) fn message(&self) -> String {
+        match &self.detail {
+            Some(detail) => format!("{}: {}", self.title, detail),
+            None => self.title.clone(),
+        }
+    }
+}
+
+/// Renders a body whose field errors are listed under `invalid-params`.
+fn invalid_params(body: ApiErrorResponse) -> Value {
+    let params = body
+        .errors
+        .iter()
+        .map(|error| {
+            let mut param = serde_json::Map::new();
+            param.insert("name".to_string(), error.pointer.clone().into());
+            param.insert("reason".to_string(), error.message.clone().into());
+            if let Some(code) = &error.code {
+                param.insert("code".to_string(), code.clone().into());
+            }
+            if let Some(params) = &error.params {
+                param.insert("params".to_string(), params.clone().into());
+            }
+            Value::Object(param)
+        })
+        .collect::<Vec<_>>();
+    let mut body = serde_json::to_value(ApiErrorResponse {
+        errors: Vec::new(),
+        ..body
+    })
+    .expect("ApiErrorResponse is always serializable");
+    body["invalid-params"] = Value::Array(params);
+    body
 }
 
+/// Optional parts of an `ApiError` that most errors leave empty.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Extras {
+    /// Additional headers to send with the error response
+    pub(crate) headers: HeaderMap,
+    /// Typed error details
+    pub(crate) details: Vec<ErrorDetail>,
+    /// Errors of individual request fields
+    pub(crate) field_errors: Vec<FieldError>,
+    /// An application-specific error code
+    pub(crate) code: Option<String>,
+    /// The part of the request that caused the error
+    pub(crate) source: Option<ErrorSource>,
+    /// A JSON-RPC error code that overrides the one derived from the status code
+    pub(crate) jsonrpc_code: Option<i64>,
+    /// The `scimType` of a SCIM error
+    pub(crate) scim_type: Option<ScimType>,
+    /// A canonical code that overrides the one mapped from the status code
+    pub(crate) canonical_code: Option<CanonicalCode>,
+}
+
+impl Extras {
+    fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+            && self.details.is_empty()
+            && self.field_errors.is_empty()
+            && self.code.is_none()
+            && self.source.is_none()
+            && self.jsonrpc_code.is_none()
+            && self.scim_type.is_none()
+            && self.canonical_code.is_none()
+    }
+}
+
+/// Headers whose meaning depends on the status code of the response, such as the
+/// `WWW-Authenticate` challenge of a 401.
+const STATUS_HEADERS: [HeaderName; 6] = [
+    header::WWW_AUTHENTICATE,
+    header::PROXY_AUTHENTICATE,
+    header::RETRY_AFTER,
+    header::ALLOW,
+    header::CONTENT_RANGE,
+    header::LOCATION,
+];
+
 /// A builder for constructing `ApiError` instances.
 ///
 /// This builder provides a fluent interface for creating `ApiError` instances with
@@ -293,6 +627,7 @@ pub struct ApiErrorBuilder {
     title: Option<String>,
     detail: Option<String>,
     meta: Option<Value>,
+    extras: Extras,
     error: Option<Error>,
 }
 
@@ -303,6 +638,7 @@ impl Clone for ApiErrorBuilder {
             title: self.title.clone(),
             detail: self.detail.clone(),
             meta: self.meta.clone(),
+            extras: self.extras.clone(),
             // anyhow::Error doesn't implement Clone, so we skip it
             error: None,
         }
@@ -420,6 +756,128 @@ impl ApiErrorBuilder {
         self
     }
 
+    /// Gets the metadata set so far, if any.
+    ///
+    /// This is mostly useful in an enricher, which can merge its own fields into
+    /// metadata that was attached before the error was built instead of replacing it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_anyhow::ApiError;
+    /// use serde_json::json;
+    ///
+    /// let builder = ApiError::builder().meta(json!({"pool": {"size": 10}}));
+    /// assert_eq!(builder.get_meta().unwrap()["pool"]["size"], 10);
+    /// ```
+    pub fn get_meta(&self) -> Option<&Value> {
+        self.meta.as_ref()
+    }
+
+    /// Sets an application-specific error code, such as `"EMAIL_TAKEN"`.
+    ///
+    /// Unlike the title, the code is meant to be stable and matched on by clients.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::ApiError;
+    ///
+    /// let error = ApiError::builder()
+    ///     .status(StatusCode::CONFLICT)
+    ///     .title("Conflict")
+    ///     .code("EMAIL_TAKEN")
+    ///     .build();
+    ///
+    /// assert_eq!(error.code(), Some("EMAIL_TAKEN"));
+    /// ```
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.extras.code = Some(code.into());
+        self
+    }
+
+    /// Adds a header to the error response.
+    ///
+    /// Headers with the same name are appended rather than replaced.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::{header, HeaderValue, StatusCode};
+    /// use axum_anyhow::ApiError;
+    ///
+    /// let error = ApiError::builder()
+    ///     .status(StatusCode::UNAUTHORIZED)
+    ///     .title("Unauthorized")
+    ///     .header(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"))
+    ///     .build();
+    ///
+    /// assert_eq!(error.headers().unwrap()[header::WWW_AUTHENTICATE], "Bearer");
+    /// ```
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.extras.headers.append(name, value);
+        self
+    }
+
+    /// Sets the `Retry-After` header to the given delay, rounded up to whole seconds.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::{header, StatusCode};
+    /// use axum_anyhow::ApiError;
+    /// use std::time::Duration;
+    ///
+    /// let error = ApiError::builder()
+    ///     .status(StatusCode::SERVICE_UNAVAILABLE)
+    ///     .title("Service Unavailable")
+    ///     .retry_after(Duration::from_millis(1500))
+    ///     .build();
+    ///
+    /// assert_eq!(error.headers().unwrap()[header::RETRY_AFTER], "2");
+    /// ```
+    pub fn retry_after(mut self, delay: Duration) -> Self {
+        let secs = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+        self.extras
+            .headers
+            .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        self
+    }
+
+    /// Returns the optional parts of the error, for builder methods defined in other
+    /// modules.
+    pub(crate) fn extras_mut(&mut self) -> &mut Extras {
+        &mut self.extras
+    }
+
+    /// Clears the title, detail, code and field errors, so that a new context can replace
+    /// them.
+    pub(crate) fn clear_context(mut self) -> Self {
+        self.title = None;
+        self.detail = None;
+        self.extras.code = None;
+        self.extras.field_errors.clear();
+        self
+    }
+
+    /// Sets a new status code and, if it differs from the current one, clears the typed
+    /// details, the source, the codes and the headers that were chosen for the old status.
+    pub(crate) fn change_status(mut self, status: StatusCode) -> Self {
+        if self.status != Some(status) {
+            for name in STATUS_HEADERS {
+                self.extras.headers.remove(name);
+            }
+            self.extras.details.clear();
+            self.extras.source = None;
+            self.extras.jsonrpc_code = None;
+            self.extras.scim_type = None;
+            self.extras.canonical_code = None;
+        }
+        self.status = Some(status);
+        self
+    }
+
     /// Builds the `ApiError` instance.
     ///
     /// If `status`, `title`, or `detail` have not been set, they will default to:
@@ -452,16 +910,24 @@ impl ApiErrorBuilder {
     pub fn build(mut self) -> ApiError {
         self = EnrichmentContext::invoke(self);
 
-        let error = ApiError {
+        let error = self.rebuild();
+        invoke_hook(&error);
+        error
+    }
+
+    /// Builds the `ApiError` without running error enrichers or the error hook.
+    ///
+    /// This is for changing an error that was already built, which the enrichers and the
+    /// hook have seen once.
+    pub(crate) fn rebuild(self) -> ApiError {
+        ApiError {
             status: self.status.unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
             title: self.title.unwrap_or_else(|| "Internal Error".to_string()),
             detail: self.detail,
             meta: self.meta,
+            extras: (!self.extras.is_empty()).then(|| Box::new(self.extras)),
             error: self.error,
-        };
-
-        invoke_hook(&error);
-        error
+        }
     }
 }
 
@@ -829,6 +1295,18 @@ mod tests {
         assert!(error.meta.is_none());
     }
 
+    #[test]
+    fn test_api_error_stays_small() {
+        // Handlers return `ApiResult<T>`, so keep below clippy's `result_large_err` threshold.
+        assert!(std::mem::size_of::<ApiError>() < 128);
+    }
+
+    #[test]
+    fn test_api_error_without_headers() {
+        let error = ApiError::builder().status(StatusCode::NOT_FOUND).build();
+        assert!(error.extras.is_none());
+    }
+
     #[tokio::test]
     async fn test_into_response_with_meta() {
         use serde_json::json;
@@ -879,6 +1357,26 @@ mod tests {
         assert!(json.get("meta").is_none());
     }
 
+    #[tokio::test]
+    async fn test_into_response_with_headers() {
+        let api_err = ApiError::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .title("Service Unavailable")
+            .retry_after(Duration::from_secs(5))
+            .header(
+                HeaderName::from_static("x-reason"),
+                HeaderValue::from_static("maintenance"),
+            )
+            .build();
+
+        let response = api_err.into_response();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "5");
+        assert_eq!(response.headers()["x-reason"], "maintenance");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    }
+
     #[test]
     fn test_api_error_builder_fluent_with_meta() {
         use serde_json::json;
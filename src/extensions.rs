@@ -78,6 +78,9 @@ use axum::http::StatusCode;
 /// }
 /// ```
 pub trait ResultExt<T>: sealed::SealedResult {
+    /// The error type of the result
+    type Error;
+
     /// Converts an error to an `ApiError` with a custom status code.
     ///
     /// # Example
@@ -356,12 +359,113 @@ pub trait ResultExt<T>: sealed::SealedResult {
     /// assert_eq!(result.unwrap_err().status(), StatusCode::GATEWAY_TIMEOUT);
     /// ```
     fn context_gateway_timeout(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Converts an error to an `ApiError` with a custom status code, building the context
+    /// lazily from the error.
+    ///
+    /// The closure only runs if the result is an error, so formatting the detail costs
+    /// nothing on the happy path.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_anyhow::{ApiResult, ResultExt};
+    /// use axum::http::StatusCode;
+    ///
+    /// let result: ApiResult<u32> = "abc"
+    ///     .parse::<u32>()
+    ///     .with_context_status(StatusCode::BAD_REQUEST, |err| {
+    ///         ("Invalid Number", format!("Could not parse 'abc': {err}"))
+    ///     });
+    ///
+    /// let err = result.unwrap_err();
+    /// assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    /// assert_eq!(err.detail(), Some("Could not parse 'abc': invalid digit found in string"));
+    /// ```
+    fn with_context_status<C, F>(self, status: StatusCode, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 400 Bad Request error, building the context lazily from the error.
+    fn with_context_bad_request<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 401 Unauthorized error, building the context lazily from the error.
+    fn with_context_unauthorized<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 403 Forbidden error, building the context lazily from the error.
+    fn with_context_forbidden<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 404 Not Found error, building the context lazily from the error.
+    fn with_context_not_found<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 405 Method Not Allowed error, building the context lazily from the error.
+    fn with_context_method_not_allowed<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 409 Conflict error, building the context lazily from the error.
+    fn with_context_conflict<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 422 Unprocessable Entity error, building the context lazily from the error.
+    fn with_context_unprocessable_entity<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 429 Too Many Requests error, building the context lazily from the error.
+    fn with_context_too_many_requests<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 500 Internal Server Error, building the context lazily from the error.
+    fn with_context_internal<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 502 Bad Gateway error, building the context lazily from the error.
+    fn with_context_bad_gateway<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 503 Service Unavailable error, building the context lazily from the error.
+    fn with_context_service_unavailable<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
+
+    /// Converts an error to a 504 Gateway Timeout error, building the context lazily from the error.
+    fn with_context_gateway_timeout<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&Self::Error) -> C;
 }
 
 impl<T, E> ResultExt<T> for Result<T, E>
 where
     E: IntoApiError,
 {
+    type Error = E;
+
     fn context_status(
         self,
         status: StatusCode,
@@ -417,6 +521,113 @@ where
     fn context_gateway_timeout(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
         self.map_err(|err| err.context_gateway_timeout(context))
     }
+
+    fn with_context_status<C, F>(self, status: StatusCode, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.map_err(|err| {
+            let context = f(&err);
+            err.context_status(status, context)
+        })
+    }
+
+    fn with_context_bad_request<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::BAD_REQUEST, f)
+    }
+
+    fn with_context_unauthorized<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::UNAUTHORIZED, f)
+    }
+
+    fn with_context_forbidden<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::FORBIDDEN, f)
+    }
+
+    fn with_context_not_found<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::NOT_FOUND, f)
+    }
+
+    fn with_context_method_not_allowed<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::METHOD_NOT_ALLOWED, f)
+    }
+
+    fn with_context_conflict<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::CONFLICT, f)
+    }
+
+    fn with_context_unprocessable_entity<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::UNPROCESSABLE_ENTITY, f)
+    }
+
+    fn with_context_too_many_requests<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::TOO_MANY_REQUESTS, f)
+    }
+
+    fn with_context_internal<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::INTERNAL_SERVER_ERROR, f)
+    }
+
+    fn with_context_bad_gateway<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::BAD_GATEWAY, f)
+    }
+
+    fn with_context_service_unavailable<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::SERVICE_UNAVAILABLE, f)
+    }
+
+    fn with_context_gateway_timeout<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce(&E) -> C,
+    {
+        self.with_context_status(StatusCode::GATEWAY_TIMEOUT, f)
+    }
 }
 
 /// Extension trait for `Option<T>` to convert `None` into `ApiError` with HTTP status codes.
@@ -765,6 +976,102 @@ pub trait OptionExt<T>: sealed::SealedOption {
     /// assert_eq!(result.unwrap_err().status(), StatusCode::GATEWAY_TIMEOUT);
     /// ```
     fn context_gateway_timeout(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Converts `None` to an `ApiError` with a custom status code, building the context
+    /// lazily.
+    ///
+    /// The closure only runs if the option is `None`, so formatting the detail costs
+    /// nothing on the happy path.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_anyhow::{ApiResult, OptionExt};
+    /// use axum::http::StatusCode;
+    ///
+    /// let id = 42;
+    /// let result: ApiResult<String> = None.with_context_status(StatusCode::NOT_FOUND, || {
+    ///     ("User Not Found", format!("No user with ID {id}"))
+    /// });
+    ///
+    /// assert_eq!(result.unwrap_err().detail(), Some("No user with ID 42"));
+    /// ```
+    fn with_context_status<C, F>(self, status: StatusCode, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 400 Bad Request error, building the context lazily.
+    fn with_context_bad_request<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 401 Unauthorized error, building the context lazily.
+    fn with_context_unauthorized<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 403 Forbidden error, building the context lazily.
+    fn with_context_forbidden<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 404 Not Found error, building the context lazily.
+    fn with_context_not_found<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 405 Method Not Allowed error, building the context lazily.
+    fn with_context_method_not_allowed<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 409 Conflict error, building the context lazily.
+    fn with_context_conflict<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 422 Unprocessable Entity error, building the context lazily.
+    fn with_context_unprocessable_entity<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 429 Too Many Requests error, building the context lazily.
+    fn with_context_too_many_requests<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 500 Internal Server Error, building the context lazily.
+    fn with_context_internal<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 502 Bad Gateway error, building the context lazily.
+    fn with_context_bad_gateway<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 503 Service Unavailable error, building the context lazily.
+    fn with_context_service_unavailable<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
+
+    /// Converts `None` to a 504 Gateway Timeout error, building the context lazily.
+    fn with_context_gateway_timeout<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C;
 }
 
 impl<T> OptionExt<T> for Option<T> {
@@ -773,13 +1080,11 @@ impl<T> OptionExt<T> for Option<T> {
         status: StatusCode,
         context: impl Into<ApiErrorContext>,
     ) -> ApiResult<T> {
-        let ctx = context.into();
         self.ok_or_else(|| {
-            let mut builder = ApiError::builder().status(status).title(ctx.title);
-            if let Some(detail) = ctx.detail {
-                builder = builder.detail(detail);
-            }
-            builder.build()
+            context
+                .into()
+                .apply(ApiError::builder().status(status))
+                .build()
         })
     }
 
@@ -830,6 +1135,738 @@ impl<T> OptionExt<T> for Option<T> {
     fn context_gateway_timeout(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
         self.context_status(StatusCode::GATEWAY_TIMEOUT, context)
     }
+
+    fn with_context_status<C, F>(self, status: StatusCode, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        match self {
+            Some(value) => Ok(value),
+            None => None.context_status(status, f()),
+        }
+    }
+
+    fn with_context_bad_request<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::BAD_REQUEST, f)
+    }
+
+    fn with_context_unauthorized<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::UNAUTHORIZED, f)
+    }
+
+    fn with_context_forbidden<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::FORBIDDEN, f)
+    }
+
+    fn with_context_not_found<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::NOT_FOUND, f)
+    }
+
+    fn with_context_method_not_allowed<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::METHOD_NOT_ALLOWED, f)
+    }
+
+    fn with_context_conflict<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::CONFLICT, f)
+    }
+
+    fn with_context_unprocessable_entity<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::UNPROCESSABLE_ENTITY, f)
+    }
+
+    fn with_context_too_many_requests<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::TOO_MANY_REQUESTS, f)
+    }
+
+    fn with_context_internal<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::INTERNAL_SERVER_ERROR, f)
+    }
+
+    fn with_context_bad_gateway<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::BAD_GATEWAY, f)
+    }
+
+    fn with_context_service_unavailable<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::SERVICE_UNAVAILABLE, f)
+    }
+
+    fn with_context_gateway_timeout<C, F>(self, f: F) -> ApiResult<T>
+    where
+        C: Into<ApiErrorContext>,
+        F: FnOnce() -> C,
+    {
+        self.with_context_status(StatusCode::GATEWAY_TIMEOUT, f)
+    }
+}
+
+/// Extension trait for `Result<Option<T>, E>` to convert both the error and `None` into
+/// `ApiError` in one call.
+///
+/// Repository functions often return `anyhow::Result<Option<T>>`, where an error means
+/// the lookup failed and `None` means nothing was found. Each `context_found_or_*`
+/// method converts an error to a 500 Internal Server Error with the first context, and
+/// `None` to the named status code with the second.
+///
+/// # Example
+///
+/// ```rust
+/// use anyhow::Result;
+/// use axum_anyhow::{ApiResult, ResultOptionExt};
+/// use axum::http::StatusCode;
+///
+/// fn find_user(id: u32) -> Result<Option<String>> {
+///     Ok((id == 1).then(|| "Alice".to_string()))
+/// }
+///
+/// fn handler(id: u32) -> ApiResult<String> {
+///     find_user(id).context_found_or_not_found(
+///         ("Lookup Failed", "Could not query the user store"),
+///         ("User Not Found", "No user with that ID exists"),
+///     )
+/// }
+///
+/// assert_eq!(handler(1).unwrap(), "Alice");
+/// let err = handler(2).unwrap_err();
+/// assert_eq!(err.status(), StatusCode::NOT_FOUND);
+/// assert_eq!(err.title(), "User Not Found");
+/// ```
+///
+/// # Sealed Trait
+///
+/// This trait is sealed and cannot be implemented for types outside this crate.
+/// This is intentional to allow adding new methods in the future without breaking changes.
+pub trait ResultOptionExt<T>: sealed::SealedResultOption {
+    /// Converts an error and `None` to `ApiError`s with custom status codes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use anyhow::{anyhow, Result};
+    /// use axum_anyhow::{ApiResult, ResultOptionExt};
+    /// use axum::http::StatusCode;
+    ///
+    /// fn find_order(id: u32) -> Result<Option<u32>> {
+    ///     Err(anyhow!("connection reset"))
+    /// }
+    ///
+    /// let result = find_order(7).context_found_or_status(
+    ///     StatusCode::SERVICE_UNAVAILABLE,
+    ///     "Order Store Unavailable",
+    ///     StatusCode::GONE,
+    ///     "Order Deleted",
+    /// );
+    ///
+    /// assert_eq!(result.unwrap_err().status(), StatusCode::SERVICE_UNAVAILABLE);
+    /// ```
+    fn context_found_or_status(
+        self,
+        err_status: StatusCode,
+        err_context: impl Into<ApiErrorContext>,
+        none_status: StatusCode,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 400 Bad Request error.
+    fn context_found_or_bad_request(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 401 Unauthorized error.
+    fn context_found_or_unauthorized(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 403 Forbidden error.
+    fn context_found_or_forbidden(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 404 Not Found error.
+    fn context_found_or_not_found(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 405 Method Not Allowed error.
+    fn context_found_or_method_not_allowed(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 409 Conflict error.
+    fn context_found_or_conflict(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 422 Unprocessable Entity error.
+    fn context_found_or_unprocessable_entity(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 429 Too Many Requests error.
+    fn context_found_or_too_many_requests(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 500 Internal Server Error.
+    fn context_found_or_internal(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 502 Bad Gateway error.
+    fn context_found_or_bad_gateway(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 503 Service Unavailable error.
+    fn context_found_or_service_unavailable(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Converts an error to a 500 Internal Server Error and `None` to a 504 Gateway Timeout error.
+    fn context_found_or_gateway_timeout(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+}
+
+impl<T, E> ResultOptionExt<T> for Result<Option<T>, E>
+where
+    E: IntoApiError,
+{
+    fn context_found_or_status(
+        self,
+        err_status: StatusCode,
+        err_context: impl Into<ApiErrorContext>,
+        none_status: StatusCode,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_status(err_status, err_context)?
+            .context_status(none_status, none_context)
+    }
+
+    fn context_found_or_bad_request(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::BAD_REQUEST, none_context)
+    }
+
+    fn context_found_or_unauthorized(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::UNAUTHORIZED, none_context)
+    }
+
+    fn context_found_or_forbidden(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::FORBIDDEN, none_context)
+    }
+
+    fn context_found_or_not_found(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::NOT_FOUND, none_context)
+    }
+
+    fn context_found_or_method_not_allowed(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::METHOD_NOT_ALLOWED, none_context)
+    }
+
+    fn context_found_or_conflict(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::CONFLICT, none_context)
+    }
+
+    fn context_found_or_unprocessable_entity(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::UNPROCESSABLE_ENTITY, none_context)
+    }
+
+    fn context_found_or_too_many_requests(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::TOO_MANY_REQUESTS, none_context)
+    }
+
+    fn context_found_or_internal(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::INTERNAL_SERVER_ERROR, none_context)
+    }
+
+    fn context_found_or_bad_gateway(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::BAD_GATEWAY, none_context)
+    }
+
+    fn context_found_or_service_unavailable(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::SERVICE_UNAVAILABLE, none_context)
+    }
+
+    fn context_found_or_gateway_timeout(
+        self,
+        err_context: impl Into<ApiErrorContext>,
+        none_context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.context_internal(err_context)?
+            .context_status(StatusCode::GATEWAY_TIMEOUT, none_context)
+    }
+}
+
+/// Extension trait for `ApiResult` to change the status code or context of an error that
+/// has already been converted.
+///
+/// Shared service functions often return `ApiResult<T>`, and callers may need to present
+/// their errors differently, for example turning a 404 into a 403 to conceal that a
+/// resource exists, or a 500 into a 502 when the service is a remote dependency.
+///
+/// The underlying error, metadata and headers are kept. When the status code changes,
+/// typed details, the error source, any canonical, JSON-RPC or SCIM code, and headers
+/// that only make sense for the old status, such as `WWW-Authenticate` and
+/// `Retry-After`, are dropped. Error enrichers
+/// and the [`on_error`](crate::on_error) hook already ran when the error was first
+/// built, so they are not run again for the changed error.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum_anyhow::{ApiResult, ApiResultExt, OptionExt};
+///
+/// fn find_document(id: u32) -> ApiResult<String> {
+///     None.context_not_found(("Not Found", format!("No document with ID {id}")))
+/// }
+///
+/// fn read_private_document(id: u32) -> ApiResult<String> {
+///     find_document(id).or_else_status(StatusCode::NOT_FOUND, |err| {
+///         Err(err).recontext_forbidden("Forbidden")
+///     })
+/// }
+///
+/// let err = read_private_document(7).unwrap_err();
+/// assert_eq!(err.status(), StatusCode::FORBIDDEN);
+/// assert_eq!(err.title(), "Forbidden");
+/// assert_eq!(err.detail(), None);
+/// ```
+///
+/// # Sealed Trait
+///
+/// This trait is sealed and cannot be implemented for types outside this crate.
+/// This is intentional to allow adding new methods in the future without breaking changes.
+pub trait ApiResultExt<T>: sealed::SealedApiResult {
+    /// Changes the status code of an error from `from` to `to`, keeping its title, detail,
+    /// code, field errors, metadata and underlying error.
+    ///
+    /// Errors with any other status code are returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::{ApiError, ApiResult, ApiResultExt};
+    ///
+    /// let result: ApiResult<()> = Err(ApiError::builder().title("Inventory Failed").build());
+    /// let err = result
+    ///     .map_status(StatusCode::INTERNAL_SERVER_ERROR, StatusCode::BAD_GATEWAY)
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(err.status(), StatusCode::BAD_GATEWAY);
+    /// assert_eq!(err.title(), "Inventory Failed");
+    /// ```
+    fn map_status(self, from: StatusCode, to: StatusCode) -> ApiResult<T>;
+
+    /// Replaces the status code and context of an error.
+    ///
+    /// The title, detail, code and field errors come from the new context only, so nothing
+    /// from the original message leaks into the response. Metadata from the context is merged into
+    /// the existing metadata.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::{ApiResult, ApiResultExt, OptionExt};
+    ///
+    /// let result: ApiResult<()> = None.context_not_found(("Not Found", "No such invoice"));
+    /// let err = result
+    ///     .recontext_status(StatusCode::GONE, ("Gone", "Invoices are archived after a year"))
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(err.status(), StatusCode::GONE);
+    /// assert_eq!(err.detail(), Some("Invoices are archived after a year"));
+    /// ```
+    fn recontext_status(
+        self,
+        status: StatusCode,
+        context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+
+    /// Calls `f` with an error that has the given status code, to recover from it or to
+    /// replace it.
+    ///
+    /// Errors with any other status code are returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::{ApiResult, ApiResultExt, OptionExt};
+    ///
+    /// fn find_avatar(user: &str) -> ApiResult<String> {
+    ///     None.context_not_found("Not Found")
+    /// }
+    ///
+    /// let avatar = find_avatar("ada")
+    ///     .or_else_status(StatusCode::NOT_FOUND, |_| Ok("default.png".to_string()))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(avatar, "default.png");
+    /// ```
+    fn or_else_status<F>(self, status: StatusCode, f: F) -> ApiResult<T>
+    where
+        F: FnOnce(ApiError) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 400 Bad Request error.
+    fn recontext_bad_request(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 401 Unauthorized error.
+    fn recontext_unauthorized(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 403 Forbidden error.
+    fn recontext_forbidden(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 404 Not Found error.
+    fn recontext_not_found(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 405 Method Not Allowed error.
+    fn recontext_method_not_allowed(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 409 Conflict error.
+    fn recontext_conflict(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 422 Unprocessable Entity error.
+    fn recontext_unprocessable_entity(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 429 Too Many Requests error.
+    fn recontext_too_many_requests(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 500 Internal Server Error.
+    fn recontext_internal(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 502 Bad Gateway error.
+    fn recontext_bad_gateway(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 503 Service Unavailable error.
+    fn recontext_service_unavailable(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Replaces the context of an error and turns it into a 504 Gateway Timeout error.
+    fn recontext_gateway_timeout(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+}
+
+impl<T> ApiResultExt<T> for ApiResult<T> {
+    fn map_status(self, from: StatusCode, to: StatusCode) -> ApiResult<T> {
+        self.or_else_status(from, |err| {
+            Err(err.into_builder().change_status(to).rebuild())
+        })
+    }
+
+    fn recontext_status(
+        self,
+        status: StatusCode,
+        context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.map_err(|err| {
+            let builder = err.into_builder().clear_context().change_status(status);
+            context.into().apply(builder).rebuild()
+        })
+    }
+
+    fn or_else_status<F>(self, status: StatusCode, f: F) -> ApiResult<T>
+    where
+        F: FnOnce(ApiError) -> ApiResult<T>,
+    {
+        match self {
+            Err(err) if err.status() == status => f(err),
+            result => result,
+        }
+    }
+
+    fn recontext_bad_request(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::BAD_REQUEST, context)
+    }
+
+    fn recontext_unauthorized(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::UNAUTHORIZED, context)
+    }
+
+    fn recontext_forbidden(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::FORBIDDEN, context)
+    }
+
+    fn recontext_not_found(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::NOT_FOUND, context)
+    }
+
+    fn recontext_method_not_allowed(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::METHOD_NOT_ALLOWED, context)
+    }
+
+    fn recontext_conflict(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::CONFLICT, context)
+    }
+
+    fn recontext_unprocessable_entity(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::UNPROCESSABLE_ENTITY, context)
+    }
+
+    fn recontext_too_many_requests(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::TOO_MANY_REQUESTS, context)
+    }
+
+    fn recontext_internal(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::INTERNAL_SERVER_ERROR, context)
+    }
+
+    fn recontext_bad_gateway(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::BAD_GATEWAY, context)
+    }
+
+    fn recontext_service_unavailable(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::SERVICE_UNAVAILABLE, context)
+    }
+
+    fn recontext_gateway_timeout(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.recontext_status(StatusCode::GATEWAY_TIMEOUT, context)
+    }
+}
+
+/// Extension trait for `bool` to turn guard conditions into `ApiError` with HTTP status
+/// codes.
+///
+/// `true` becomes `Ok(())` and `false` becomes an error with the given status code and
+/// context, so checks read as a single line in a handler.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::{ApiResult, BoolExt};
+/// use axum::http::StatusCode;
+///
+/// fn delete_user(is_admin: bool) -> ApiResult<()> {
+///     is_admin.or_forbidden(("Forbidden", "Only admins can delete users"))?;
+///     Ok(())
+/// }
+///
+/// assert!(delete_user(true).is_ok());
+/// let err = delete_user(false).unwrap_err();
+/// assert_eq!(err.status(), StatusCode::FORBIDDEN);
+/// assert_eq!(err.detail(), Some("Only admins can delete users"));
+/// ```
+///
+/// # Sealed Trait
+///
+/// This trait is sealed and cannot be implemented for types outside this crate.
+/// This is intentional to allow adding new methods in the future without breaking changes.
+pub trait BoolExt: sealed::SealedBool {
+    /// Converts `false` to an `ApiError` with a custom status code.
+    fn or_status(self, status: StatusCode, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 400 Bad Request error.
+    fn or_bad_request(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 401 Unauthorized error (missing or invalid credentials).
+    fn or_unauthorized(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 403 Forbidden error (authenticated but lacks permissions).
+    fn or_forbidden(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 404 Not Found error.
+    fn or_not_found(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 405 Method Not Allowed error.
+    fn or_method_not_allowed(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 409 Conflict error.
+    fn or_conflict(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 422 Unprocessable Entity error.
+    fn or_unprocessable_entity(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 429 Too Many Requests error.
+    fn or_too_many_requests(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 500 Internal Server Error.
+    fn or_internal(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 502 Bad Gateway error.
+    fn or_bad_gateway(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 503 Service Unavailable error.
+    fn or_service_unavailable(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+
+    /// Converts `false` to a 504 Gateway Timeout error.
+    fn or_gateway_timeout(self, context: impl Into<ApiErrorContext>) -> ApiResult<()>;
+}
+
+impl BoolExt for bool {
+    fn or_status(self, status: StatusCode, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.then_some(()).context_status(status, context)
+    }
+
+    fn or_bad_request(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::BAD_REQUEST, context)
+    }
+
+    fn or_unauthorized(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::UNAUTHORIZED, context)
+    }
+
+    fn or_forbidden(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::FORBIDDEN, context)
+    }
+
+    fn or_not_found(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::NOT_FOUND, context)
+    }
+
+    fn or_method_not_allowed(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::METHOD_NOT_ALLOWED, context)
+    }
+
+    fn or_conflict(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::CONFLICT, context)
+    }
+
+    fn or_unprocessable_entity(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::UNPROCESSABLE_ENTITY, context)
+    }
+
+    fn or_too_many_requests(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::TOO_MANY_REQUESTS, context)
+    }
+
+    fn or_internal(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::INTERNAL_SERVER_ERROR, context)
+    }
+
+    fn or_bad_gateway(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::BAD_GATEWAY, context)
+    }
+
+    fn or_service_unavailable(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::SERVICE_UNAVAILABLE, context)
+    }
+
+    fn or_gateway_timeout(self, context: impl Into<ApiErrorContext>) -> ApiResult<()> {
+        self.or_status(StatusCode::GATEWAY_TIMEOUT, context)
+    }
 }
 
 /// Extension trait for converting any error type into `ApiError` with HTTP status codes.
@@ -924,15 +1961,10 @@ where
     E: Into<anyhow::Error>,
 {
     fn context_status(self, status: StatusCode, context: impl Into<ApiErrorContext>) -> ApiError {
-        let ctx = context.into();
-        let mut builder = ApiError::builder()
-            .status(status)
-            .title(ctx.title)
-            .error(self);
-        if let Some(detail) = ctx.detail {
-            builder = builder.detail(detail);
-        }
-        builder.build()
+        context
+            .into()
+            .apply(ApiError::builder().status(status).error(self))
+            .build()
     }
 
     fn context_bad_request(self, context: impl Into<ApiErrorContext>) -> ApiError {
@@ -990,10 +2022,16 @@ mod sealed {
     pub trait SealedResult {}
     pub trait SealedOption {}
     pub trait SealedIntoApiError {}
+    pub trait SealedBool {}
+    pub trait SealedResultOption {}
+    pub trait SealedApiResult {}
 
     impl<T, E> SealedResult for Result<T, E> where E: IntoApiError {}
     impl<T> SealedOption for Option<T> {}
     impl<E> SealedIntoApiError for E where E: Into<anyhow::Error> {}
+    impl SealedBool for bool {}
+    impl<T, E> SealedResultOption for Result<Option<T>, E> where E: IntoApiError {}
+    impl<T> SealedApiResult for crate::ApiResult<T> {}
 }
 
 #[cfg(test)]
@@ -1046,6 +2084,92 @@ mod tests {
         assert_eq!(err.detail(), Some("Value must be a number"));
     }
 
+    #[test]
+    fn test_result_ext_with_context_uses_error() {
+        let result: Result<u32> = Err(anyhow!("disk full"));
+        let err = result
+            .with_context_service_unavailable(|err| {
+                ("Storage Unavailable", format!("Cause: {err}"))
+            })
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(err.detail(), Some("Cause: disk full"));
+        assert!(err.error().is_some());
+    }
+
+    #[test]
+    fn test_result_ext_with_context_is_lazy_on_ok() {
+        let result: Result<u32> = Ok(1);
+        let value = result
+            .with_context_internal(|_| -> &str { panic!("context built on the happy path") })
+            .unwrap();
+        assert_eq!(value, 1);
+    }
+
+    #[test]
+    fn test_option_ext_with_context() {
+        let some = Some(1).with_context_not_found(|| -> &str { panic!("context built on Some") });
+        assert_eq!(some.unwrap(), 1);
+
+        let err = None::<u32>
+            .with_context_not_found(|| ("Not Found", format!("No item {}", 7)))
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+        assert_eq!(err.detail(), Some("No item 7"));
+    }
+
+    #[test]
+    fn test_result_option_ext_found() {
+        let result: Result<Option<u32>> = Ok(Some(1));
+        let value = result
+            .context_found_or_not_found("Lookup Failed", "Not Found")
+            .unwrap();
+        assert_eq!(value, 1);
+    }
+
+    #[test]
+    fn test_result_option_ext_none() {
+        let result: Result<Option<u32>> = Ok(None);
+        let err = result
+            .context_found_or_not_found("Lookup Failed", ("Not Found", "No such item"))
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+        assert_eq!(err.title(), "Not Found");
+        assert_eq!(err.detail(), Some("No such item"));
+        assert!(err.error().is_none());
+    }
+
+    #[test]
+    fn test_result_option_ext_err() {
+        let result: Result<Option<u32>> = Err(anyhow!("connection reset"));
+        let err = result
+            .context_found_or_conflict("Lookup Failed", "Conflict")
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(err.title(), "Lookup Failed");
+        assert!(err.error().is_some());
+    }
+
+    #[test]
+    fn test_bool_ext_or_forbidden() {
+        assert!(true.or_forbidden("Forbidden").is_ok());
+
+        let err = false
+            .or_forbidden(("Forbidden", "Admin access required"))
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::FORBIDDEN);
+        assert_eq!(err.title(), "Forbidden");
+        assert_eq!(err.detail(), Some("Admin access required"));
+    }
+
+    #[test]
+    fn test_bool_ext_or_status() {
+        let err = false
+            .or_status(StatusCode::PRECONDITION_FAILED, "Precondition Failed")
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
     #[test]
     fn test_option_ext_context_bad_request_on_none() {
         let option: Option<i32> = None;
@@ -1172,4 +2296,183 @@ mod tests {
         assert_eq!(err.title(), "Bad Request");
         assert_eq!(err.detail(), Some("Invalid input"));
     }
+
+    fn lookup_failure() -> ApiResult<()> {
+        Err(ApiError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Not Found")
+            .detail("No user with ID 42")
+            .code("USER_NOT_FOUND")
+            .meta(serde_json::json!({"trace_id": "abc"}))
+            .error(anyhow!("row not found"))
+            .build())
+    }
+
+    #[test]
+    fn test_map_status_keeps_everything_else() {
+        let err = lookup_failure()
+            .map_status(StatusCode::NOT_FOUND, StatusCode::BAD_GATEWAY)
+            .unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(err.title(), "Not Found");
+        assert_eq!(err.detail(), Some("No user with ID 42"));
+        assert_eq!(err.code(), Some("USER_NOT_FOUND"));
+        assert_eq!(err.error().unwrap().to_string(), "row not found");
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_map_status_runs_hook_and_enricher_once() {
+        use crate::{on_error, ErrorInterceptorLayer};
+        use axum::{body::Body, extract::Request, routing::get, Router};
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+        use tower::ServiceExt;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        on_error({
+            let calls = calls.clone();
+            move |err| {
+                if err.title() == "Hidden Document" {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        });
+
+        async fn handler() -> ApiResult<()> {
+            Err(crate::not_found("Hidden Document", "No document with ID 7"))
+                .map_status(StatusCode::NOT_FOUND, StatusCode::FORBIDDEN)
+                .recontext_status(StatusCode::FORBIDDEN, "Hidden Document")
+        }
+
+        let app = Router::new()
+            .route("/", get(handler))
+            .layer(ErrorInterceptorLayer::new(|builder, _| {
+                builder.header(
+                    axum::http::HeaderName::from_static("x-request-id"),
+                    axum::http::HeaderValue::from_static("abc"),
+                )
+            }));
+        let response = app
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        on_error(|_| {});
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(response.headers().get_all("x-request-id").iter().count(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_map_status_ignores_other_statuses() {
+        let err = lookup_failure()
+            .map_status(StatusCode::INTERNAL_SERVER_ERROR, StatusCode::BAD_GATEWAY)
+            .unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_recontext_replaces_title_detail_and_code() {
+        let err = lookup_failure()
+            .recontext_forbidden("Forbidden")
+            .unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::FORBIDDEN);
+        assert_eq!(err.title(), "Forbidden");
+        assert_eq!(err.detail(), None);
+        assert_eq!(err.code(), None);
+        assert_eq!(err.meta().unwrap()["trace_id"], "abc");
+        assert_eq!(err.error().unwrap().to_string(), "row not found");
+    }
+
+    #[test]
+    fn test_status_change_drops_status_specific_details() {
+        let hidden = || {
+            Err::<(), _>(
+                ApiError::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .title("Not Found")
+                    .error_info("USER_NOT_FOUND", "example.com")
+                    .source_parameter("id")
+                    .jsonrpc_code(-32004)
+                    .canonical_code(crate::CanonicalCode::NotFound)
+                    .meta(serde_json::json!({"trace_id": "abc"}))
+                    .error(anyhow!("row not found"))
+                    .build(),
+            )
+        };
+
+        let err = hidden().recontext_forbidden("Forbidden").unwrap_err();
+        assert!(err.details().is_empty());
+        assert!(err.source().is_none());
+        assert_eq!(err.jsonrpc_code(), -32003);
+        assert_eq!(err.canonical_code(), crate::CanonicalCode::PermissionDenied);
+        assert_eq!(err.meta().unwrap()["trace_id"], "abc");
+        assert_eq!(err.error().unwrap().to_string(), "row not found");
+
+        let err = hidden()
+            .map_status(StatusCode::NOT_FOUND, StatusCode::FORBIDDEN)
+            .unwrap_err();
+        assert!(err.details().is_empty());
+        assert_eq!(err.title(), "Not Found");
+
+        let body = crate::google::to_json(&err);
+        assert!(!body.to_string().contains("USER_NOT_FOUND"));
+    }
+
+    #[test]
+    fn test_status_change_drops_status_specific_headers() {
+        use axum::{
+            http::{header, HeaderName, HeaderValue},
+            response::IntoResponse,
+        };
+
+        let unauthorized = || {
+            Err::<(), _>(
+                ApiError::builder()
+                    .status(StatusCode::UNAUTHORIZED)
+                    .title("Unauthorized")
+                    .header(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"))
+                    .header(
+                        HeaderName::from_static("x-request-id"),
+                        HeaderValue::from_static("abc"),
+                    )
+                    .field_error(crate::FieldError::new("/token", "Expired"))
+                    .build(),
+            )
+        };
+
+        let err = unauthorized().recontext_not_found("Not Found").unwrap_err();
+        assert!(err.field_errors().is_empty());
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response.headers().get(header::WWW_AUTHENTICATE).is_none());
+        assert_eq!(response.headers()["x-request-id"], "abc");
+
+        let err = unauthorized()
+            .map_status(StatusCode::UNAUTHORIZED, StatusCode::NOT_FOUND)
+            .unwrap_err();
+        assert_eq!(err.field_errors().len(), 1);
+        assert!(err
+            .into_response()
+            .headers()
+            .get(header::WWW_AUTHENTICATE)
+            .is_none());
+    }
+
+    #[test]
+    fn test_or_else_status_recovers_matching_error() {
+        let recovered = lookup_failure().or_else_status(StatusCode::NOT_FOUND, |_| Ok(()));
+        assert!(recovered.is_ok());
+
+        let err = lookup_failure()
+            .or_else_status(StatusCode::CONFLICT, |_| Ok(()))
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    }
 }
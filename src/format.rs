@@ -0,0 +1,170 @@
+use std::{cell::Cell, sync::RwLock};
+
+/// The wire format used to render `ApiError` response bodies.
+///
+/// The format applies to every `ApiError` returned from a handler. Set it once at
+/// startup with [`set_error_format`], or render a single error in a specific format
+/// with [`ApiError::into_response_as`](crate::ApiError::into_response_as).
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::{error_format, set_error_format, ErrorFormat};
+///
+/// set_error_format(ErrorFormat::Google);
+/// assert_eq!(error_format(), ErrorFormat::Google);
+/// # set_error_format(ErrorFormat::Problem);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorFormat {
+    /// The default JSON body with `status`, `title`, `detail` and `meta` fields,
+    /// modelled on [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457.html).
+    #[default]
+    Problem,
+    /// The `google.rpc.Status` JSON body from Google's API design guide
+    /// ([AIP-193](https://google.aip.dev/193)).
+    Google,
+    /// The `errors` array from the [JSON:API](https://jsonapi.org/format/#errors)
+    /// specification, sent as `application/vnd.api+json`.
+    JsonApi,
+    /// A [JSON-RPC 2.0](https://www.jsonrpc.org/specification) response with an `error`
+    /// object and a `null` id, sent with `200 OK`.
+    JsonRpc,
+    /// A [Connect](https://connectrpc.com/docs/protocol#error-end-stream) error with a
+    /// lowercase code such as `not_found`, and the metadata in a `google.rpc.ErrorInfo`
+    /// detail.
+    Connect,
+    /// A [Twirp](https://twitchtv.github.io/twirp/docs/spec_v7.html#error-codes) error
+    /// with a lowercase code such as `not_found`, and the metadata in `meta`.
+    Twirp,
+    /// The body of Spring Boot's `DefaultErrorAttributes`, with `timestamp`, `status`,
+    /// `error`, `message` and `path` fields.
+    Spring,
+    /// The `{"detail": ...}` body of FastAPI, with a list of `loc`, `msg` and `type`
+    /// objects for field errors.
+    FastApi,
+    /// The `{"detail": ...}` body of Django REST Framework, with a map from field names
+    /// to messages for field errors.
+    Drf,
+    /// A [SCIM 2.0](https://www.rfc-editor.org/rfc/rfc7644#section-3.12) error message
+    /// with a string `status` and an optional `scimType`, sent as
+    /// `application/scim+json`.
+    Scim,
+    /// The `{"error": {"code", "message", "target", "details"}}` body of
+    /// [OData](https://docs.oasis-open.org/odata/odata-json-format/v4.01/odata-json-format-v4.01.html#sec_ErrorResponse)
+    /// and the Microsoft REST API Guidelines, with the chain of the underlying error in
+    /// `innererror` when error details are exposed.
+    OData,
+    /// The default body as an [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457.html)
+    /// XML problem document, sent as `application/problem+xml`.
+    #[cfg(feature = "xml")]
+    ProblemXml,
+    /// An S3-style `<Error>` document with `Code`, `Message`, `Resource` and `RequestId`
+    /// elements, sent as `application/xml`.
+    #[cfg(feature = "xml")]
+    S3,
+    /// The fields of the default body encoded as a MessagePack map, sent as
+    /// `application/msgpack`.
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+    /// The fields of the default body encoded as a CBOR map, sent as `application/cbor`.
+    #[cfg(feature = "cbor")]
+    Cbor,
+    /// The fields of the default body encoded as the `axum_anyhow.v1.Error` protobuf
+    /// message, sent as `application/x-protobuf`.
+    #[cfg(feature = "protobuf")]
+    Protobuf,
+}
+
+static ERROR_FORMAT: RwLock<ErrorFormat> = RwLock::new(ErrorFormat::Problem);
+
+thread_local! {
+    static ROUTE_ERROR_FORMAT: Cell<Option<ErrorFormat>> = const { Cell::new(None) };
+}
+
+/// Sets the format used to render `ApiError` response bodies.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::{set_error_format, ErrorFormat};
+///
+/// set_error_format(ErrorFormat::Google);
+/// # set_error_format(ErrorFormat::Problem);
+/// ```
+pub fn set_error_format(format: ErrorFormat) {
+    let mut guard = ERROR_FORMAT
+        .write()
+        .expect("Failed to get write lock for ErrorFormat");
+    *guard = format;
+}
+
+/// Returns the format currently used to render `ApiError` response bodies.
+///
+/// Inside a router with an [`ErrorFormatLayer`](crate::ErrorFormatLayer), this is the
+/// format chosen by the layer for the current request.
+pub fn error_format() -> ErrorFormat {
+    if let Some(format) = ROUTE_ERROR_FORMAT.with(Cell::get) {
+        return format;
+    }
+    *ERROR_FORMAT
+        .read()
+        .expect("Failed to get read lock for ErrorFormat")
+}
+
+/// Runs `f` with `format` as the error format of the current thread, restoring the
+/// previous one afterwards, even if `f` panics.
+pub(crate) fn with_error_format<R>(format: ErrorFormat, f: impl FnOnce() -> R) -> R {
+    let previous = ROUTE_ERROR_FORMAT.with(|cell| cell.replace(Some(format)));
+    let _guard = FormatGuard(previous);
+    f()
+}
+
+/// Restores the previous route error format when dropped, even if `f` panics.
+struct FormatGuard(Option<ErrorFormat>);
+
+impl Drop for FormatGuard {
+    fn drop(&mut self) {
+        ROUTE_ERROR_FORMAT.with(|cell| cell.set(self.0));
+    }
+}
+
+/// How field errors are named in the default response body.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::{set_field_error_format, FieldErrorFormat};
+///
+/// set_field_error_format(FieldErrorFormat::InvalidParams);
+/// # set_field_error_format(FieldErrorFormat::Errors);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FieldErrorFormat {
+    /// An `errors` array of `{"pointer", "code", "message", "params"}` objects.
+    #[default]
+    Errors,
+    /// The `invalid-params` array from the examples of
+    /// [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457.html#section-3), with
+    /// `{"name", "reason"}` objects.
+    InvalidParams,
+}
+
+static FIELD_ERROR_FORMAT: RwLock<FieldErrorFormat> = RwLock::new(FieldErrorFormat::Errors);
+
+/// Sets how field errors are named in the default response body.
+pub fn set_field_error_format(format: FieldErrorFormat) {
+    let mut guard = FIELD_ERROR_FORMAT
+        .write()
+        .expect("Failed to get write lock for FieldErrorFormat");
+    *guard = format;
+}
+
+/// Returns how field errors are currently named in the default response body.
+pub fn field_error_format() -> FieldErrorFormat {
+    *FIELD_ERROR_FORMAT
+        .read()
+        .expect("Failed to get read lock for FieldErrorFormat")
+}
@@ -0,0 +1,197 @@
+//! Propagation of errors returned by upstream `axum-anyhow` services.
+//!
+//! A gateway or backend-for-frontend that calls another service built with this crate
+//! can decode the upstream error body and decide how much of it to pass on to its own
+//! clients.
+
+use crate::{ApiError, ApiErrorBuilder, ApiErrorResponse};
+use anyhow::anyhow;
+use axum::http::StatusCode;
+use serde_json::json;
+
+/// Decides how an upstream error response is propagated to the caller.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum_anyhow::PropagationPolicy;
+///
+/// let body = br#"{"status": 503, "title": "Service Unavailable"}"#;
+/// let error = PropagationPolicy::Gateway.apply(StatusCode::SERVICE_UNAVAILABLE, body);
+///
+/// assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+/// assert_eq!(error.meta().unwrap()["upstream"]["status"], 503);
+///
+/// let body = br#"{"status": 404, "title": "Not Found"}"#;
+/// let error = PropagationPolicy::Gateway.apply(StatusCode::NOT_FOUND, body);
+///
+/// assert_eq!(error.status(), StatusCode::NOT_FOUND);
+/// assert_eq!(error.title(), "Not Found");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PropagationPolicy {
+    /// Pass every upstream error through unchanged.
+    PassThrough,
+    /// Pass 4xx errors through unchanged and wrap 5xx errors as `502 Bad Gateway`,
+    /// with the upstream error in `meta.upstream`.
+    #[default]
+    Gateway,
+    /// Replace the status code of every upstream error, keeping its title, detail and
+    /// metadata.
+    Rewrite(StatusCode),
+}
+
+impl PropagationPolicy {
+    /// Builds an `ApiError` from the status and body of an upstream error response.
+    ///
+    /// If the body is not an `axum-anyhow` error, the canonical reason of the status
+    /// code is used as the title.
+    pub fn apply(&self, status: StatusCode, body: &[u8]) -> ApiError {
+        let (response, error) = match serde_json::from_slice::<ApiErrorResponse>(body) {
+            Ok(response) => {
+                let error = match &response.detail {
+                    Some(detail) => anyhow!("Upstream error {}: {}", response.title, detail),
+                    None => anyhow!("Upstream error {}", response.title),
+                };
+                (response, error)
+            }
+            Err(err) => {
+                let response = ApiErrorResponse::new(
+                    status.as_u16(),
+                    status.canonical_reason().unwrap_or("Unknown"),
+                );
+                let error = anyhow!(err).context(format!(
+                    "Upstream returned status {status} with an unrecognized error body"
+                ));
+                (response, error)
+            }
+        };
+
+        let builder = ApiError::builder().error(error);
+        let builder = match self {
+            PropagationPolicy::Gateway if status.is_server_error() => builder
+                .status(StatusCode::BAD_GATEWAY)
+                .title("Bad Gateway")
+                .meta(json!({
+                    "upstream": {
+                        "status": status.as_u16(),
+                        "title": response.title,
+                        "detail": response.detail,
+                        "meta": response.meta,
+                    }
+                })),
+            PropagationPolicy::PassThrough | PropagationPolicy::Gateway => {
+                passthrough(builder.status(status), response)
+            }
+            PropagationPolicy::Rewrite(rewritten) => {
+                passthrough(builder.status(*rewritten), response)
+            }
+        };
+        builder.build()
+    }
+}
+
+fn passthrough(mut builder: ApiErrorBuilder, response: ApiErrorResponse) -> ApiErrorBuilder {
+    builder = builder.title(response.title);
+    if let Some(detail) = response.detail {
+        builder = builder.detail(detail);
+    }
+    if let Some(meta) = response.meta {
+        builder = builder.meta(meta);
+    }
+    builder.field_errors(response.errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+    use http_body_util::BodyExt;
+    use serial_test::serial;
+
+    async fn round_trip(error: ApiError) -> (StatusCode, Vec<u8>) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, bytes.to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_from_response_round_trip() {
+        let upstream = ApiError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("Conflict")
+            .detail("Email already registered")
+            .meta(json!({"field": "email"}))
+            .build();
+        let (status, body) = round_trip(upstream).await;
+
+        let error = ApiError::from_response(status, &body);
+
+        assert_eq!(error.status(), StatusCode::CONFLICT);
+        assert_eq!(error.title(), "Conflict");
+        assert_eq!(error.detail(), Some("Email already registered"));
+        assert_eq!(error.meta().unwrap()["field"], "email");
+        assert!(error.error().is_some());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_from_response_keeps_field_errors() {
+        let upstream = ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("Validation Failed")
+            .field_error(crate::FieldError::new("/email", "Email must contain @"))
+            .build();
+        let (status, body) = round_trip(upstream).await;
+
+        let error = ApiError::from_response(status, &body);
+
+        assert_eq!(error.field_errors()[0].pointer, "/email");
+    }
+
+    #[test]
+    fn test_from_response_unrecognized_body() {
+        let error = ApiError::from_response(StatusCode::BAD_GATEWAY, b"<html>oops</html>");
+
+        assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(error.title(), "Bad Gateway");
+        assert_eq!(error.detail(), None);
+        assert!(error.meta().is_none());
+    }
+
+    #[test]
+    fn test_gateway_passes_client_errors_through() {
+        let body = br#"{"status": 422, "title": "Invalid", "detail": "Bad email"}"#;
+        let error = PropagationPolicy::Gateway.apply(StatusCode::UNPROCESSABLE_ENTITY, body);
+
+        assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.title(), "Invalid");
+        assert_eq!(error.detail(), Some("Bad email"));
+    }
+
+    #[test]
+    fn test_gateway_wraps_server_errors() {
+        let body = br#"{"status": 500, "title": "Internal Error", "detail": "Boom"}"#;
+        let error = PropagationPolicy::Gateway.apply(StatusCode::INTERNAL_SERVER_ERROR, body);
+
+        assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(error.title(), "Bad Gateway");
+        assert_eq!(error.detail(), None);
+        let upstream = &error.meta().unwrap()["upstream"];
+        assert_eq!(upstream["status"], 500);
+        assert_eq!(upstream["title"], "Internal Error");
+        assert_eq!(upstream["detail"], "Boom");
+    }
+
+    #[test]
+    fn test_rewrite_replaces_status() {
+        let body = br#"{"status": 404, "title": "Not Found"}"#;
+        let error =
+            PropagationPolicy::Rewrite(StatusCode::FORBIDDEN).apply(StatusCode::NOT_FOUND, body);
+
+        assert_eq!(error.status(), StatusCode::FORBIDDEN);
+        assert_eq!(error.title(), "Not Found");
+    }
+}
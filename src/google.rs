@@ -0,0 +1,528 @@
+//! Google API design guide error format ([AIP-193](https://google.aip.dev/193)).
+//!
+//! Errors are rendered as a `google.rpc.Status` JSON object with typed details:
+//!
+//! ```json
+//! {
+//!   "error": {
+//!     "code": 404,
+//!     "message": "User Not Found: No user with ID 42",
+//!     "status": "NOT_FOUND",
+//!     "details": [
+//!       {
+//!         "@type": "type.googleapis.com/google.rpc.ErrorInfo",
+//!         "reason": "USER_NOT_FOUND",
+//!         "domain": "users.example.com",
+//!         "metadata": { "user_id": "42" }
+//!       }
+//!     ]
+//!   }
+//! }
+//! ```
+//!
+//! AIP-193 requires an `ErrorInfo` on every error, so one is derived from the title of
+//! the error when none was added, with the domain set by [`set_error_domain`].
+
+use crate::{naming::reason, ApiError, ApiErrorBuilder, CanonicalCode};
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Map, Value};
+use std::{collections::BTreeMap, sync::RwLock, time::Duration};
+
+const ERROR_INFO_TYPE: &str = "type.googleapis.com/google.rpc.ErrorInfo";
+const BAD_REQUEST_TYPE: &str = "type.googleapis.com/google.rpc.BadRequest";
+const RETRY_INFO_TYPE: &str = "type.googleapis.com/google.rpc.RetryInfo";
+const HELP_TYPE: &str = "type.googleapis.com/google.rpc.Help";
+
+static ERROR_DOMAIN: RwLock<String> = RwLock::new(String::new());
+
+/// Sets the domain of the `ErrorInfo` sent with errors that have no explicit
+/// [`error_info`](ApiErrorBuilder::error_info), or whose `error_info` has an empty
+/// domain.
+///
+/// The domain is the logical grouping of the service that generates errors, typically
+/// its DNS name, such as `"users.example.com"`. It is empty until set.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::{error_domain, set_error_domain};
+///
+/// set_error_domain("users.example.com");
+/// assert_eq!(error_domain(), "users.example.com");
+/// # set_error_domain("");
+/// ```
+pub fn set_error_domain(domain: impl Into<String>) {
+    let mut guard = ERROR_DOMAIN
+        .write()
+        .expect("Failed to get write lock for error domain");
+    *guard = domain.into();
+}
+
+/// Returns the domain set with [`set_error_domain`].
+pub fn error_domain() -> String {
+    ERROR_DOMAIN
+        .read()
+        .expect("Failed to get read lock for error domain")
+        .clone()
+}
+
+/// A typed error detail, modelled on the standard `google.rpc` error detail messages.
+///
+/// Details are added with the builder methods [`error_info`](ApiErrorBuilder::error_info),
+/// [`field_violation`](ApiErrorBuilder::field_violation),
+/// [`retry_info`](ApiErrorBuilder::retry_info) and [`help_link`](ApiErrorBuilder::help_link).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorDetail {
+    /// The cause of the error, as a `google.rpc.ErrorInfo`
+    ErrorInfo(ErrorInfo),
+    /// Violations of fields in the request, as a `google.rpc.BadRequest`
+    BadRequest(Vec<FieldViolation>),
+    /// How long the client should wait before retrying, as a `google.rpc.RetryInfo`
+    RetryInfo(Duration),
+    /// Links to documentation, as a `google.rpc.Help`
+    Help(Vec<HelpLink>),
+}
+
+/// The cause of an error.
+///
+/// The metadata of the error is sent as the `metadata` of the `ErrorInfo`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorInfo {
+    /// A constant `UPPER_SNAKE_CASE` identifier of the cause of the error
+    pub reason: String,
+    /// The logical grouping of the reason, usually the name of the service
+    pub domain: String,
+}
+
+/// A single invalid field in a request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldViolation {
+    /// A path to the field, such as `"user.email"`
+    pub field: String,
+    /// Why the field is invalid
+    pub description: String,
+}
+
+/// A link to documentation about an error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HelpLink {
+    /// What the link offers
+    pub description: String,
+    /// The URL of the link
+    pub url: String,
+}
+
+impl ApiErrorBuilder {
+    /// Sets the `ErrorInfo` detail of the error.
+    ///
+    /// Without an explicit `ErrorInfo`, one is derived from the code or title when the
+    /// error has metadata.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::{ApiError, ErrorDetail};
+    ///
+    /// let error = ApiError::builder()
+    ///     .status(StatusCode::NOT_FOUND)
+    ///     .title("User Not Found")
+    ///     .error_info("USER_NOT_FOUND", "users.example.com")
+    ///     .build();
+    ///
+    /// assert!(matches!(&error.details()[0], ErrorDetail::ErrorInfo(info) if info.domain == "users.example.com"));
+    /// ```
+    pub fn error_info(mut self, reason: impl Into<String>, domain: impl Into<String>) -> Self {
+        let info = ErrorInfo {
+            reason: reason.into(),
+            domain: domain.into(),
+        };
+        let details = &mut self.extras_mut().details;
+        details.retain(|detail| !matches!(detail, ErrorDetail::ErrorInfo(_)));
+        details.push(ErrorDetail::ErrorInfo(info));
+        self
+    }
+
+    /// Adds a field violation to the `BadRequest` detail of the error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::{ApiError, ErrorDetail};
+    ///
+    /// let error = ApiError::builder()
+    ///     .status(StatusCode::BAD_REQUEST)
+    ///     .title("Invalid Request")
+    ///     .field_violation("email", "Email must contain @")
+    ///     .field_violation("age", "Age must be positive")
+    ///     .build();
+    ///
+    /// assert!(matches!(&error.details()[0], ErrorDetail::BadRequest(violations) if violations.len() == 2));
+    /// ```
+    pub fn field_violation(
+        mut self,
+        field: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        let violation = FieldViolation {
+            field: field.into(),
+            description: description.into(),
+        };
+        let details = &mut self.extras_mut().details;
+        match details.iter_mut().find_map(|detail| match detail {
+            ErrorDetail::BadRequest(violations) => Some(violations),
+            _ => None,
+        }) {
+            Some(violations) => violations.push(violation),
+            None => details.push(ErrorDetail::BadRequest(vec![violation])),
+        }
+        self
+    }
+
+    /// Sets the `RetryInfo` detail of the error.
+    ///
+    /// This only affects the body. Use [`retry_after`](ApiErrorBuilder::retry_after) to
+    /// also send a `Retry-After` header.
+    pub fn retry_info(mut self, delay: Duration) -> Self {
+        let details = &mut self.extras_mut().details;
+        details.retain(|detail| !matches!(detail, ErrorDetail::RetryInfo(_)));
+        details.push(ErrorDetail::RetryInfo(delay));
+        self
+    }
+
+    /// Adds a link to the `Help` detail of the error.
+    pub fn help_link(mut self, description: impl Into<String>, url: impl Into<String>) -> Self {
+        let link = HelpLink {
+            description: description.into(),
+            url: url.into(),
+        };
+        let details = &mut self.extras_mut().details;
+        match details.iter_mut().find_map(|detail| match detail {
+            ErrorDetail::Help(links) => Some(links),
+            _ => None,
+        }) {
+            Some(links) => links.push(link),
+            None => details.push(ErrorDetail::Help(vec![link])),
+        }
+        self
+    }
+}
+
+/// Renders an `ApiError` as a `google.rpc.Status` JSON response.
+pub(crate) fn into_response(error: ApiError) -> Response {
+    let status = error.status();
+    (status, Json(to_json(&error))).into_response()
+}
+
+/// Builds the `google.rpc.Status` document.
+pub(crate) fn to_json(error: &ApiError) -> Value {
+    let mut details = Vec::new();
+    let metadata = error.meta().map(metadata).unwrap_or_default();
+
+    if !error
+        .details()
+        .iter()
+        .any(|detail| matches!(detail, ErrorDetail::ErrorInfo(_)))
+    {
+        let mut value = json!({
+            "@type": ERROR_INFO_TYPE,
+            "reason": error
+                .code()
+                .map_or_else(|| reason(error.title(), error.canonical_code()), str::to_string),
+            "domain": error_domain(),
+        });
+        if !metadata.is_empty() {
+            value["metadata"] = json!(metadata);
+        }
+        details.push(value);
+    }
+
+    for detail in error.details() {
+        details.push(match detail {
+            ErrorDetail::ErrorInfo(info) => {
+                let mut value = json!({
+                    "@type": ERROR_INFO_TYPE,
+                    "reason": info.reason,
+                    "domain": domain(info),
+                });
+                if !metadata.is_empty() {
+                    value["metadata"] = json!(metadata);
+                }
+                value
+            }
+            ErrorDetail::BadRequest(_) => bad_request(error),
+            ErrorDetail::RetryInfo(delay) => json!({
+                "@type": RETRY_INFO_TYPE,
+                "retryDelay": duration(*delay),
+            }),
+            ErrorDetail::Help(links) => json!({
+                "@type": HELP_TYPE,
+                "links": links
+                    .iter()
+                    .map(|link| json!({
+                        "description": link.description,
+                        "url": link.url,
+                    }))
+                    .collect::<Vec<_>>(),
+            }),
+        });
+    }
+
+    if !has_bad_request(error) && !error.field_errors().is_empty() {
+        details.push(bad_request(error));
+    }
+
+    let mut body = Map::new();
+    body.insert("code".to_string(), json!(error.status().as_u16()));
+    body.insert("message".to_string(), json!(error.message()));
+    // An error body never says OK, even for an error built with a 2xx status.
+    let status = match error.canonical_code() {
+        CanonicalCode::Ok => CanonicalCode::Unknown,
+        code => code,
+    };
+    body.insert("status".to_string(), json!(status.as_str()));
+    if !details.is_empty() {
+        body.insert("details".to_string(), Value::Array(details));
+    }
+    json!({ "error": body })
+}
+
+fn has_bad_request(error: &ApiError) -> bool {
+    error
+        .details()
+        .iter()
+        .any(|detail| matches!(detail, ErrorDetail::BadRequest(_)))
+}
+
+fn bad_request(error: &ApiError) -> Value {
+    json!({
+        "@type": BAD_REQUEST_TYPE,
+        "fieldViolations": field_violations(error)
+            .iter()
+            .map(|violation| json!({
+                "field": violation.field,
+                "description": violation.description,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Returns the field violations of an error, followed by its field errors.
+pub(crate) fn field_violations(error: &ApiError) -> Vec<FieldViolation> {
+    let violations = error.details().iter().filter_map(|detail| match detail {
+        ErrorDetail::BadRequest(violations) => Some(violations),
+        _ => None,
+    });
+    let field_errors = error
+        .field_errors()
+        .iter()
+        .map(|field_error| FieldViolation {
+            field: field_error.segments().join("."),
+            description: field_error.message.clone(),
+        });
+    violations.flatten().cloned().chain(field_errors).collect()
+}
+
+/// Formats a duration as a protobuf JSON `Duration`, e.g. `"1.500s"`.
+fn duration(delay: Duration) -> String {
+    match delay.subsec_nanos() {
+        0 => format!("{}s", delay.as_secs()),
+        nanos => {
+            let fraction = format!("{nanos:09}");
+            format!("{}.{}s", delay.as_secs(), fraction.trim_end_matches('0'))
+        }
+    }
+}
+
+/// Returns the domain of an `ErrorInfo`, or the one set with [`set_error_domain`] if it
+/// is empty.
+pub(crate) fn domain(info: &ErrorInfo) -> String {
+    match info.domain.as_str() {
+        "" => error_domain(),
+        domain => domain.to_string(),
+    }
+}
+
+/// Flattens error metadata into the string map of an `ErrorInfo`.
+pub(crate) fn metadata(meta: &Value) -> BTreeMap<String, String> {
+    let to_string = |value: &Value| match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    };
+
+    match meta {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| (key.clone(), to_string(value)))
+            .collect(),
+        Value::Null => BTreeMap::new(),
+        value => BTreeMap::from([("meta".to_string(), to_string(value))]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorFormat;
+    use axum::http::StatusCode;
+    use http_body_util::BodyExt;
+    use serial_test::serial;
+
+    async fn body(error: ApiError) -> Value {
+        let response = error.into_response_as(ErrorFormat::Google);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_google_format_basic() {
+        set_error_domain("users.example.com");
+        let error = ApiError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Not Found")
+            .detail("No user with ID 42")
+            .build();
+
+        assert_eq!(
+            body(error).await,
+            json!({
+                "error": {
+                    "code": 404,
+                    "message": "Not Found: No user with ID 42",
+                    "status": "NOT_FOUND",
+                    "details": [{
+                        "@type": ERROR_INFO_TYPE,
+                        "reason": "NOT_FOUND",
+                        "domain": "users.example.com",
+                    }],
+                }
+            })
+        );
+        set_error_domain("");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_google_format_error_info_without_domain() {
+        set_error_domain("users.example.com");
+        let error = ApiError::builder()
+            .status(StatusCode::FORBIDDEN)
+            .title("Forbidden")
+            .error_info("QUOTA_DENIED", "")
+            .build();
+
+        assert_eq!(
+            body(error).await["error"]["details"][0]["domain"],
+            "users.example.com"
+        );
+        set_error_domain("");
+    }
+
+    #[tokio::test]
+    async fn test_google_format_error_info_with_meta() {
+        let error = ApiError::builder()
+            .status(StatusCode::FORBIDDEN)
+            .title("Forbidden")
+            .error_info("QUOTA_DENIED", "billing.example.com")
+            .meta(json!({"plan": "free", "limit": 10}))
+            .build();
+
+        assert_eq!(
+            body(error).await["error"]["details"],
+            json!([{
+                "@type": ERROR_INFO_TYPE,
+                "reason": "QUOTA_DENIED",
+                "domain": "billing.example.com",
+                "metadata": {"plan": "free", "limit": "10"},
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_google_format_derives_error_info_from_meta() {
+        let error = ApiError::builder()
+            .status(StatusCode::CONFLICT)
+            .title("Email Taken")
+            .meta(json!({"email": "a@example.com"}))
+            .build();
+
+        let details = &body(error).await["error"]["details"];
+        assert_eq!(details[0]["reason"], "EMAIL_TAKEN");
+        assert_eq!(details[0]["metadata"]["email"], "a@example.com");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_google_format_typed_details() {
+        let error = ApiError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("Invalid Request")
+            .field_violation("email", "Email must contain @")
+            .field_violation("age", "Age must be positive")
+            .retry_info(Duration::from_millis(1500))
+            .help_link("API docs", "https://example.com/docs")
+            .build();
+
+        assert_eq!(
+            body(error).await["error"]["details"],
+            json!([
+                {"@type": ERROR_INFO_TYPE, "reason": "INVALID_REQUEST", "domain": ""},
+                {
+                    "@type": BAD_REQUEST_TYPE,
+                    "fieldViolations": [
+                        {"field": "email", "description": "Email must contain @"},
+                        {"field": "age", "description": "Age must be positive"},
+                    ],
+                },
+                {"@type": RETRY_INFO_TYPE, "retryDelay": "1.5s"},
+                {
+                    "@type": HELP_TYPE,
+                    "links": [{"description": "API docs", "url": "https://example.com/docs"}],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_google_format_field_errors_use_field_paths() {
+        let error = ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("404")
+            .field_error(crate::FieldError::new("/address/city", "Required"))
+            .field_error(crate::FieldError::new("/tags/0", "Too long"))
+            .field_error(crate::FieldError::new("page", "Must be a number"))
+            .build();
+
+        let details = &to_json(&error)["error"]["details"];
+        assert_eq!(details[0]["reason"], "INVALID_ARGUMENT");
+        let fields = details[1]["fieldViolations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|violation| violation["field"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(fields, ["address.city", "tags.0", "page"]);
+    }
+
+    #[test]
+    fn test_duration_format() {
+        assert_eq!(duration(Duration::from_secs(30)), "30s");
+        assert_eq!(duration(Duration::from_millis(250)), "0.25s");
+    }
+
+    #[test]
+    fn test_google_format_2xx_status_is_unknown() {
+        let error = ApiError::builder()
+            .status(StatusCode::OK)
+            .title("Partial Failure")
+            .build();
+
+        assert_eq!(to_json(&error)["error"]["status"], "UNKNOWN");
+    }
+}
@@ -0,0 +1,164 @@
+//! Conversion of `ApiError` into `async-graphql` errors.
+//!
+//! GraphQL resolvers can share domain code with REST handlers and use `?` on an
+//! `ApiResult`. The status code, title and code of the error become `extensions` of the
+//! GraphQL error:
+//!
+//! ```json
+//! {
+//!   "message": "User Not Found: No user with ID 42",
+//!   "extensions": { "code": "NOT_FOUND", "status": 404, "title": "User Not Found" }
+//! }
+//! ```
+
+use crate::ApiError;
+use async_graphql::{ErrorExtensionValues, Value};
+use std::sync::Arc;
+
+/// Converts an `ApiError` into an `async_graphql::Error`.
+///
+/// The message combines the title and the detail, just like the body of a REST response
+/// would. Underlying error messages only appear in the detail when
+/// [`set_expose_errors`](crate::set_expose_errors) is enabled, and the underlying error
+/// itself is kept in the `source` as an `anyhow::Error`, which is never sent to the
+/// client.
+///
+/// The extensions are:
+///
+/// - `code`: the code set with [`ApiErrorBuilder::code`](crate::ApiErrorBuilder::code),
+///   or the reason phrase of the status code in upper snake case, such as `NOT_FOUND`
+/// - `status`: the HTTP status code
+/// - `title`: the title
+/// - `meta`: the metadata, if any
+///
+/// # Example
+///
+/// ```rust
+/// use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+/// use axum_anyhow::{ApiResult, OptionExt};
+///
+/// fn find_user(id: u32) -> ApiResult<String> {
+///     (id == 1).then(|| "Alice".to_string()).context_not_found("User Not Found")
+/// }
+///
+/// struct Query;
+///
+/// #[Object]
+/// impl Query {
+///     async fn user(&self, id: u32) -> async_graphql::Result<String> {
+///         Ok(find_user(id)?)
+///     }
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+/// let response = schema.execute("{ user(id: 2) }").await;
+///
+/// let extensions = serde_json::to_value(&response.errors[0].extensions).unwrap();
+/// assert_eq!(extensions["code"], "NOT_FOUND");
+/// assert_eq!(extensions["status"], 404);
+/// # });
+/// ```
+impl From<ApiError> for async_graphql::Error {
+    fn from(error: ApiError) -> Self {
+        let mut extensions = ErrorExtensionValues::default();
+        let code = error.code().map_or_else(
+            || {
+                crate::naming::reason(
+                    error.status().canonical_reason().unwrap_or("Unknown"),
+                    error.canonical_code(),
+                )
+            },
+            str::to_string,
+        );
+        extensions.set("code", code);
+        extensions.set("status", error.status().as_u16());
+        extensions.set("title", error.title());
+        if let Some(meta) = error
+            .meta()
+            .and_then(|meta| Value::from_json(meta.clone()).ok())
+        {
+            extensions.set("meta", meta);
+        }
+
+        async_graphql::Error {
+            message: error.message(),
+            source: Some(Arc::new(error.into_error())),
+            extensions: Some(extensions),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_expose_errors, ResultExt};
+    use anyhow::anyhow;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+    use axum::http::StatusCode;
+    use serde_json::json;
+    use serial_test::serial;
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn order(&self) -> async_graphql::Result<u32> {
+            let order = Err(anyhow!("row 7 missing"))
+                .context_status(StatusCode::CONFLICT, ("Order Locked", "Try again later"))?;
+            Ok(order)
+        }
+
+        async fn total(&self) -> async_graphql::Result<u32> {
+            Ok(Err::<u32, _>(anyhow!("db password rejected")).map_err(ApiError::from)?)
+        }
+    }
+
+    #[test]
+    fn test_code_meta_and_source() {
+        let error = async_graphql::Error::from(
+            ApiError::builder()
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .title("Slow Down")
+                .code("RATE_LIMITED")
+                .meta(json!({"limit": 10}))
+                .error(anyhow!("bucket empty"))
+                .build(),
+        );
+
+        assert_eq!(error.message, "Slow Down");
+        assert_eq!(
+            serde_json::to_value(&error.extensions).unwrap(),
+            json!({"code": "RATE_LIMITED", "status": 429, "title": "Slow Down", "meta": {"limit": 10}})
+        );
+        let source = error.source.unwrap();
+        let source = source.downcast_ref::<anyhow::Error>().unwrap();
+        assert_eq!(source.root_cause().to_string(), "bucket empty");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_resolver_errors_become_graphql_errors() {
+        set_expose_errors(false);
+        let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+        let response = schema.execute("{ order }").await;
+        let body = serde_json::to_value(&response).unwrap();
+
+        assert_eq!(
+            body["errors"][0]["message"],
+            "Order Locked: Try again later"
+        );
+        assert_eq!(
+            body["errors"][0]["extensions"],
+            json!({"code": "CONFLICT", "status": 409, "title": "Order Locked"})
+        );
+
+        let response = schema.execute("{ total }").await;
+        let body = serde_json::to_value(&response).unwrap();
+        assert_eq!(body["errors"][0]["message"], "Internal Error");
+        assert_eq!(
+            body["errors"][0]["extensions"]["code"],
+            "INTERNAL_SERVER_ERROR"
+        );
+    }
+}
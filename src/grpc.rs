@@ -0,0 +1,363 @@
+//! Conversions between `ApiError` and `tonic::Status`.
+//!
+//! Services that expose the same domain code over axum and tonic can keep returning
+//! `ApiResult` and convert at the transport boundary. Status codes are mapped through
+//! [`CanonicalCode`], the title and detail become the status message, and the metadata
+//! is carried in a `google.rpc.ErrorInfo` detail.
+//!
+//! There is no `From<Status> for ApiError`, since a `Status` is also an
+//! `std::error::Error` and would go through the blanket conversion to a 500 Internal
+//! Error. [`GrpcResultExt`] converts a `Status` with its gRPC code instead.
+
+use crate::{
+    google::{domain, error_domain, field_violations, metadata},
+    naming::reason,
+    ApiError, ApiResult, CanonicalCode, ErrorDetail,
+};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use tonic::{Code, Status};
+use tonic_types::{ErrorDetails, StatusExt};
+
+impl From<CanonicalCode> for Code {
+    fn from(code: CanonicalCode) -> Self {
+        match code {
+            CanonicalCode::Ok => Code::Ok,
+            CanonicalCode::Cancelled => Code::Cancelled,
+            CanonicalCode::Unknown => Code::Unknown,
+            CanonicalCode::InvalidArgument => Code::InvalidArgument,
+            CanonicalCode::DeadlineExceeded => Code::DeadlineExceeded,
+            CanonicalCode::NotFound => Code::NotFound,
+            CanonicalCode::AlreadyExists => Code::AlreadyExists,
+            CanonicalCode::PermissionDenied => Code::PermissionDenied,
+            CanonicalCode::ResourceExhausted => Code::ResourceExhausted,
+            CanonicalCode::FailedPrecondition => Code::FailedPrecondition,
+            CanonicalCode::Aborted => Code::Aborted,
+            CanonicalCode::OutOfRange => Code::OutOfRange,
+            CanonicalCode::Unimplemented => Code::Unimplemented,
+            CanonicalCode::Internal => Code::Internal,
+            CanonicalCode::Unavailable => Code::Unavailable,
+            CanonicalCode::DataLoss => Code::DataLoss,
+            CanonicalCode::Unauthenticated => Code::Unauthenticated,
+        }
+    }
+}
+
+impl From<Code> for CanonicalCode {
+    fn from(code: Code) -> Self {
+        match code {
+            Code::Ok => CanonicalCode::Ok,
+            Code::Cancelled => CanonicalCode::Cancelled,
+            Code::Unknown => CanonicalCode::Unknown,
+            Code::InvalidArgument => CanonicalCode::InvalidArgument,
+            Code::DeadlineExceeded => CanonicalCode::DeadlineExceeded,
+            Code::NotFound => CanonicalCode::NotFound,
+            Code::AlreadyExists => CanonicalCode::AlreadyExists,
+            Code::PermissionDenied => CanonicalCode::PermissionDenied,
+            Code::ResourceExhausted => CanonicalCode::ResourceExhausted,
+            Code::FailedPrecondition => CanonicalCode::FailedPrecondition,
+            Code::Aborted => CanonicalCode::Aborted,
+            Code::OutOfRange => CanonicalCode::OutOfRange,
+            Code::Unimplemented => CanonicalCode::Unimplemented,
+            Code::Internal => CanonicalCode::Internal,
+            Code::Unavailable => CanonicalCode::Unavailable,
+            Code::DataLoss => CanonicalCode::DataLoss,
+            Code::Unauthenticated => CanonicalCode::Unauthenticated,
+        }
+    }
+}
+
+/// Converts from `ApiError` to a `tonic::Status`.
+///
+/// This allows `?` on an `ApiResult` inside a tonic service method. The message is the
+/// title, followed by the detail if there is one. Typed [`ErrorDetail`]s are sent as
+/// the matching `google.rpc` details. If the error has metadata, it is sent in the
+/// `google.rpc.ErrorInfo` detail, whose reason defaults to the title in
+/// `UPPER_SNAKE_CASE`. The code is the [`canonical_code`](ApiError::canonical_code) of
+/// the error. A 2xx status, which would map to `OK`, is sent as `UNKNOWN` so
+/// that the error never reaches the client as a successful call.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::not_found;
+/// use tonic::{Code, Status};
+///
+/// let status: Status = not_found("User Not Found", "No user with ID 42").into();
+/// assert_eq!(status.code(), Code::NotFound);
+/// assert_eq!(status.message(), "User Not Found: No user with ID 42");
+/// ```
+impl From<ApiError> for Status {
+    fn from(error: ApiError) -> Self {
+        let code = match error.canonical_code() {
+            CanonicalCode::Ok => Code::Unknown,
+            code => code.into(),
+        };
+        let metadata: HashMap<_, _> = error
+            .meta()
+            .map(metadata)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let mut details = ErrorDetails::new();
+
+        if !metadata.is_empty() {
+            let reason = error.code().map_or_else(
+                || reason(error.title(), error.canonical_code()),
+                str::to_string,
+            );
+            details.set_error_info(reason, error_domain(), metadata.clone());
+        }
+        for detail in error.details() {
+            match detail {
+                ErrorDetail::ErrorInfo(info) => {
+                    details.set_error_info(&info.reason, domain(info), metadata.clone());
+                }
+                // Sent below, together with the field errors
+                ErrorDetail::BadRequest(_) => {}
+                ErrorDetail::RetryInfo(delay) => {
+                    details.set_retry_info(Some(*delay));
+                }
+                ErrorDetail::Help(links) => {
+                    for link in links {
+                        details.add_help_link(&link.description, &link.url);
+                    }
+                }
+            }
+        }
+
+        for violation in field_violations(&error) {
+            details.add_bad_request_violation(violation.field, violation.description);
+        }
+
+        if metadata.is_empty() && error.details().is_empty() && error.field_errors().is_empty() {
+            Status::new(code, error.message())
+        } else {
+            Status::with_error_details(code, error.message(), details)
+        }
+    }
+}
+
+impl ApiError {
+    /// Creates an `ApiError` from a `tonic::Status`.
+    ///
+    /// The gRPC code is mapped to its HTTP status code. A message of the form
+    /// `"title: detail"` is split back into title and detail, and the metadata of a
+    /// `google.rpc.ErrorInfo` detail becomes the error metadata.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::ApiError;
+    /// use tonic::Status;
+    ///
+    /// let error = ApiError::from_grpc_status(Status::already_exists("Conflict: Email taken"));
+    /// assert_eq!(error.status(), StatusCode::CONFLICT);
+    /// assert_eq!(error.title(), "Conflict");
+    /// assert_eq!(error.detail(), Some("Email taken"));
+    /// ```
+    pub fn from_grpc_status(status: Status) -> ApiError {
+        let code: CanonicalCode = status.code().into();
+        let mut builder = ApiError::builder().status(code.to_http());
+        if CanonicalCode::from_http(code.to_http()) != code {
+            builder = builder.canonical_code(code);
+        }
+
+        builder = match status.message().split_once(": ") {
+            Some((title, detail)) => builder.title(title).detail(detail),
+            None if status.message().is_empty() => builder.title(code.as_str()),
+            None => builder.title(status.message()),
+        };
+
+        if let Some(info) = status.get_details_error_info() {
+            let meta = info
+                .metadata
+                .into_iter()
+                .map(|(key, value)| (key, Value::String(value)))
+                .collect::<Map<_, _>>();
+            builder = builder.meta(Value::Object(meta));
+        }
+
+        builder.error(status).build()
+    }
+}
+
+/// Extension trait for results of calls to gRPC services.
+///
+/// Using `?` directly on a `Result<T, Status>` in a function returning `ApiResult`
+/// turns every status into a 500 Internal Error, like any other error. This trait maps
+/// the status with [`ApiError::from_grpc_status`] instead, keeping its code, message and
+/// metadata.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum_anyhow::{ApiResult, GrpcResultExt};
+/// use tonic::Status;
+///
+/// fn get_user() -> ApiResult<String> {
+///     let response: Result<String, Status> = Err(Status::not_found("User Not Found"));
+///     let user = response.grpc_context()?;
+///     Ok(user)
+/// }
+///
+/// assert_eq!(get_user().unwrap_err().status(), StatusCode::NOT_FOUND);
+/// ```
+///
+/// # Sealed Trait
+///
+/// This trait is sealed and cannot be implemented for types outside this crate.
+pub trait GrpcResultExt<T>: sealed::SealedGrpcResult {
+    /// Converts a `tonic::Status` error to an `ApiError` with the matching HTTP status.
+    fn grpc_context(self) -> ApiResult<T>;
+}
+
+impl<T> GrpcResultExt<T> for Result<T, Status> {
+    fn grpc_context(self) -> ApiResult<T> {
+        self.map_err(ApiError::from_grpc_status)
+    }
+}
+
+mod sealed {
+    use tonic::Status;
+
+    pub trait SealedGrpcResult {}
+
+    impl<T> SealedGrpcResult for Result<T, Status> {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    #[test]
+    fn test_api_error_into_status() {
+        let error = ApiError::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .title("Rate Limited")
+            .build();
+
+        let status: Status = error.into();
+
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(status.message(), "Rate Limited");
+        assert!(status.get_details_error_info().is_none());
+    }
+
+    #[test]
+    fn test_success_status_is_not_sent_as_ok() {
+        let error = ApiError::builder()
+            .status(StatusCode::OK)
+            .title("Not An Error")
+            .build();
+
+        let status: Status = error.into();
+
+        assert_eq!(status.code(), Code::Unknown);
+    }
+
+    #[test]
+    fn test_meta_becomes_error_info() {
+        let error = ApiError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("User Not Found")
+            .meta(json!({"user_id": 42, "tenant": "acme"}))
+            .build();
+
+        let status: Status = error.into();
+        let info = status.get_details_error_info().unwrap();
+
+        assert_eq!(info.reason, "USER_NOT_FOUND");
+        assert_eq!(info.metadata["user_id"], "42");
+        assert_eq!(info.metadata["tenant"], "acme");
+    }
+
+    #[test]
+    fn test_typed_details_become_status_details() {
+        let error = ApiError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("Invalid Request")
+            .error_info("INVALID_EMAIL", "users.example.com")
+            .field_violation("email", "Email must contain @")
+            .meta(json!({"user_id": 42}))
+            .build();
+
+        let status: Status = error.into();
+        let info = status.get_details_error_info().unwrap();
+        let bad_request = status.get_details_bad_request().unwrap();
+
+        assert_eq!(info.reason, "INVALID_EMAIL");
+        assert_eq!(info.domain, "users.example.com");
+        assert_eq!(info.metadata["user_id"], "42");
+        assert_eq!(bad_request.field_violations[0].field, "email");
+    }
+
+    #[test]
+    fn test_status_into_api_error_round_trip() {
+        let original = ApiError::builder()
+            .status(StatusCode::FORBIDDEN)
+            .title("Forbidden")
+            .detail("Admin access required")
+            .meta(json!({"role": "viewer"}))
+            .build();
+
+        let error = ApiError::from_grpc_status(original.into());
+
+        assert_eq!(error.status(), StatusCode::FORBIDDEN);
+        assert_eq!(error.title(), "Forbidden");
+        assert_eq!(error.detail(), Some("Admin access required"));
+        assert_eq!(error.meta().unwrap()["role"], "viewer");
+        assert!(error.error().is_some());
+    }
+
+    #[test]
+    fn test_aborted_round_trip() {
+        let error = ApiError::from_grpc_status(Status::aborted("Transaction Aborted"));
+        assert_eq!(error.status(), StatusCode::CONFLICT);
+
+        let status: Status = error.into();
+        assert_eq!(status.code(), Code::Aborted);
+
+        let status: Status = crate::conflict("Conflict", "Email taken").into();
+        assert_eq!(status.code(), Code::AlreadyExists);
+    }
+
+    #[test]
+    fn test_empty_message_uses_code_name() {
+        let error = ApiError::from_grpc_status(Status::new(Code::Unavailable, ""));
+
+        assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(error.title(), "UNAVAILABLE");
+        assert_eq!(error.detail(), None);
+    }
+
+    #[test]
+    fn test_grpc_context_keeps_code() {
+        fn handler() -> crate::ApiResult<()> {
+            Err(Status::permission_denied(
+                "Forbidden: Admin access required",
+            ))
+            .grpc_context()
+        }
+
+        let error = handler().unwrap_err();
+        assert_eq!(error.status(), StatusCode::FORBIDDEN);
+        assert_eq!(error.title(), "Forbidden");
+        assert_eq!(error.detail(), Some("Admin access required"));
+    }
+
+    #[test]
+    fn test_question_mark_in_tonic_method() {
+        fn handler() -> Result<(), Status> {
+            let value: Option<u32> = None;
+            crate::OptionExt::context_not_found(value, "Not Found")?;
+            Ok(())
+        }
+
+        assert_eq!(handler().unwrap_err().code(), Code::NotFound);
+    }
+}
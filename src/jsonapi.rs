@@ -0,0 +1,378 @@
+//! [JSON:API](https://jsonapi.org/format/#errors) error objects.
+//!
+//! Errors are rendered as a top-level `errors` array and sent as
+//! `application/vnd.api+json`:
+//!
+//! ```json
+//! {
+//!   "errors": [
+//!     {
+//!       "id": "4f3c2a1b9e8d7c6f",
+//!       "status": "422",
+//!       "code": "INVALID_EMAIL",
+//!       "title": "Invalid Attribute",
+//!       "detail": "Email must contain @",
+//!       "source": { "pointer": "/data/attributes/email" }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! The `id` of an error object is the `request_id` of the error metadata, such as one
+//! added by an [`ErrorInterceptorLayer`](crate::ErrorInterceptorLayer), followed by the
+//! index of the object when there are several. Without a request ID, each error object
+//! gets a random `id`.
+
+use crate::{ApiError, ApiErrorBuilder, ErrorDetail};
+use axum::{
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+const JSON_API_CONTENT_TYPE: &str = "application/vnd.api+json";
+
+/// The part of the request that caused an error.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum_anyhow::{ApiError, ErrorSource};
+///
+/// let error = ApiError::builder()
+///     .status(StatusCode::BAD_REQUEST)
+///     .title("Invalid Query Parameter")
+///     .source_parameter("sort")
+///     .build();
+///
+/// assert_eq!(error.source(), Some(&ErrorSource::Parameter("sort".to_string())));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorSource {
+    /// A JSON Pointer ([RFC 6901](https://www.rfc-editor.org/rfc/rfc6901)) to a value in
+    /// the request body, such as `"/data/attributes/email"`
+    Pointer(String),
+    /// The name of a query parameter
+    Parameter(String),
+    /// The name of a request header
+    Header(String),
+}
+
+impl ApiErrorBuilder {
+    /// Points the error at a value in the request body with a JSON Pointer.
+    pub fn source_pointer(mut self, pointer: impl Into<String>) -> Self {
+        self.extras_mut().source = Some(ErrorSource::Pointer(pointer.into()));
+        self
+    }
+
+    /// Points the error at a query parameter.
+    pub fn source_parameter(mut self, parameter: impl Into<String>) -> Self {
+        self.extras_mut().source = Some(ErrorSource::Parameter(parameter.into()));
+        self
+    }
+
+    /// Points the error at a request header.
+    pub fn source_header(mut self, header: impl Into<String>) -> Self {
+        self.extras_mut().source = Some(ErrorSource::Header(header.into()));
+        self
+    }
+}
+
+/// Renders an `ApiError` as a JSON:API error document.
+pub(crate) fn into_response(error: ApiError) -> Response {
+    let status = error.status();
+    let mut response = (status, Json(to_json(&error))).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(JSON_API_CONTENT_TYPE),
+    );
+    response
+}
+
+/// Builds the `errors` document.
+///
+/// Each field violation and field error becomes its own error object, pointing at the
+/// invalid field. Errors without either become a single error object.
+pub(crate) fn to_json(error: &ApiError) -> Value {
+    let violations = error
+        .details()
+        .iter()
+        .filter_map(|detail| match detail {
+            ErrorDetail::BadRequest(violations) => Some(violations),
+            _ => None,
+        })
+        .flatten()
+        .map(|violation| {
+            let source = ErrorSource::Pointer(pointer(&violation.field));
+            error_object(error, None, Some(&violation.description), Some(&source))
+        });
+    let field_errors = error.field_errors().iter().map(|field_error| {
+        let source = if field_error.pointer.starts_with('/') {
+            ErrorSource::Pointer(field_error.pointer.clone())
+        } else {
+            ErrorSource::Parameter(field_error.pointer.clone())
+        };
+        let mut object = error_object(
+            error,
+            field_error.code.as_deref(),
+            Some(&field_error.message),
+            Some(&source),
+        );
+        if let Some(params) = &field_error.params {
+            match &mut object["meta"] {
+                Value::Object(meta) => meta.extend(params.clone()),
+                meta @ Value::Null => *meta = Value::Object(params.clone()),
+                _ => {}
+            }
+        }
+        object
+    });
+
+    let mut errors = violations.chain(field_errors).collect::<Vec<_>>();
+    if errors.is_empty() {
+        errors.push(error_object(error, None, error.detail(), error.source()));
+    }
+
+    let request_id = error.meta().and_then(|meta| match &meta["request_id"] {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    });
+    let several = errors.len() > 1;
+    for (index, object) in errors.iter_mut().enumerate() {
+        let id = match &request_id {
+            Some(request_id) if several => format!("{request_id}-{index}"),
+            Some(request_id) => request_id.clone(),
+            None => random_id(),
+        };
+        object["id"] = json!(id);
+    }
+
+    json!({ "errors": errors })
+}
+
+/// Returns a random identifier for an error object, such as `"4f3c2a1b9e8d7c6f"`.
+fn random_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let hash = RandomState::new().hash_one(COUNTER.fetch_add(1, Ordering::Relaxed));
+    format!("{hash:016x}")
+}
+
+fn error_object(
+    error: &ApiError,
+    code: Option<&str>,
+    detail: Option<&str>,
+    source: Option<&ErrorSource>,
+) -> Value {
+    let mut object = Map::new();
+    object.insert("status".to_string(), json!(error.status().as_str()));
+    if let Some(code) = code.or(error.code()) {
+        object.insert("code".to_string(), json!(code));
+    }
+    object.insert("title".to_string(), json!(error.title()));
+    if let Some(detail) = detail {
+        object.insert("detail".to_string(), json!(detail));
+    }
+    if let Some(source) = source {
+        let source = match source {
+            ErrorSource::Pointer(pointer) => json!({ "pointer": pointer }),
+            ErrorSource::Parameter(parameter) => json!({ "parameter": parameter }),
+            ErrorSource::Header(header) => json!({ "header": header }),
+        };
+        object.insert("source".to_string(), source);
+    }
+    if let Some(meta) = error.meta() {
+        object.insert("meta".to_string(), meta.clone());
+    }
+    Value::Object(object)
+}
+
+/// Converts a field path such as `"address.city"` to a JSON Pointer into the resource
+/// attributes, such as `"/data/attributes/address/city"`. Paths that are already JSON
+/// Pointers are kept as they are.
+fn pointer(field: &str) -> String {
+    if field.starts_with('/') {
+        return field.to_string();
+    }
+    field
+        .split('.')
+        .fold("/data/attributes".to_string(), |pointer, segment| {
+            format!(
+                "{pointer}/{}",
+                segment.replace('~', "~0").replace('/', "~1")
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorFormat;
+    use axum::http::StatusCode;
+    use http_body_util::BodyExt;
+
+    async fn body(error: ApiError) -> Value {
+        let response = error.into_response_as(ErrorFormat::JsonApi);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            JSON_API_CONTENT_TYPE
+        );
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_json_api_single_error() {
+        let error = ApiError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Not Found")
+            .detail("No article with ID 1")
+            .code("ARTICLE_NOT_FOUND")
+            .meta(json!({"id": 1, "request_id": "req-7"}))
+            .build();
+
+        assert_eq!(
+            body(error).await,
+            json!({
+                "errors": [{
+                    "id": "req-7",
+                    "status": "404",
+                    "code": "ARTICLE_NOT_FOUND",
+                    "title": "Not Found",
+                    "detail": "No article with ID 1",
+                    "meta": {"id": 1, "request_id": "req-7"},
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_api_ids() {
+        let error = ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("Invalid Attribute")
+            .meta(json!({"request_id": 42}))
+            .field_violation("email", "Email must contain @")
+            .field_violation("name", "Name is required")
+            .build();
+        let body = to_json(&error);
+        assert_eq!(body["errors"][0]["id"], "42-0");
+        assert_eq!(body["errors"][1]["id"], "42-1");
+
+        let error = ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("Invalid Attribute")
+            .field_violation("email", "Email must contain @")
+            .field_violation("name", "Name is required")
+            .build();
+        let body = to_json(&error);
+        let first = body["errors"][0]["id"].as_str().unwrap();
+        let second = body["errors"][1]["id"].as_str().unwrap();
+        assert_eq!(first.len(), 16);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_json_api_field_error_params_merged_into_meta() {
+        let error = ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("Invalid Attribute")
+            .meta(json!({"trace_id": "abc"}))
+            .field_error(
+                crate::FieldError::new("/data/attributes/age", "Too young").param("min", json!(18)),
+            )
+            .build();
+
+        assert_eq!(
+            to_json(&error)["errors"][0]["meta"],
+            json!({"trace_id": "abc", "min": 18})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_api_source() {
+        let error = ApiError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("Invalid Header")
+            .source_header("X-Tenant")
+            .build();
+
+        assert_eq!(
+            body(error).await["errors"][0]["source"],
+            json!({"header": "X-Tenant"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_api_one_error_per_field_violation() {
+        let error = ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("Invalid Attribute")
+            .field_violation("email", "Email must contain @")
+            .field_violation("address.city", "City is required")
+            .build();
+
+        let body = body(error).await;
+        let errors = body["errors"].as_array().unwrap();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0]["title"], "Invalid Attribute");
+        assert_eq!(errors[0]["detail"], "Email must contain @");
+        assert_eq!(errors[0]["source"]["pointer"], "/data/attributes/email");
+        assert_eq!(
+            errors[1]["source"]["pointer"],
+            "/data/attributes/address/city"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_api_field_errors() {
+        let error = ApiError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("Invalid Request")
+            .field_error(crate::FieldError::new("/data/attributes/age", "Too young").code("range"))
+            .field_error(crate::FieldError::new("page", "Must be a number"))
+            .build();
+
+        let body = body(error).await;
+        let errors = body["errors"].as_array().unwrap();
+
+        assert_eq!(errors[0]["code"], "range");
+        assert_eq!(
+            errors[0]["source"],
+            json!({"pointer": "/data/attributes/age"})
+        );
+        assert_eq!(errors[1]["detail"], "Must be a number");
+        assert_eq!(errors[1]["source"], json!({"parameter": "page"}));
+    }
+
+    #[tokio::test]
+    async fn test_json_api_keeps_extra_headers() {
+        let error = ApiError::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .retry_after(std::time::Duration::from_secs(3))
+            .build();
+
+        let response = error.into_response_as(ErrorFormat::JsonApi);
+
+        assert_eq!(response.headers()[header::RETRY_AFTER], "3");
+    }
+
+    #[test]
+    fn test_pointer_from_field_path() {
+        assert_eq!(pointer("title"), "/data/attributes/title");
+        assert_eq!(
+            pointer("/data/relationships/author"),
+            "/data/relationships/author"
+        );
+        assert_eq!(pointer("a/b"), "/data/attributes/a~1b");
+    }
+}
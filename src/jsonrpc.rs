@@ -0,0 +1,417 @@
+//! [JSON-RPC 2.0](https://www.jsonrpc.org/specification) error objects.
+//!
+//! JSON-RPC errors are sent with `200 OK`, and the status code of an `ApiError` is
+//! mapped to a JSON-RPC error code instead:
+//!
+//! ```json
+//! {
+//!   "jsonrpc": "2.0",
+//!   "error": {
+//!     "code": -32601,
+//!     "message": "Method Not Found",
+//!     "data": { "status": 404, "detail": "No method named 'users.delete'" }
+//!   },
+//!   "id": 7
+//! }
+//! ```
+//!
+//! [`json_rpc`] dispatches single and batch requests to a handler that returns an
+//! `ApiResult`, so the same `ResultExt` code works for RPC methods.
+
+use crate::{ApiError, ApiErrorBuilder, ApiResult};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::{json, Map, Value};
+use std::future::Future;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const SERVER_ERROR: i64 = -32000;
+
+impl ApiErrorBuilder {
+    /// Sets the JSON-RPC error code, overriding the one derived from the status code.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::ApiError;
+    ///
+    /// let error = ApiError::builder()
+    ///     .status(StatusCode::CONFLICT)
+    ///     .title("Insufficient Funds")
+    ///     .jsonrpc_code(1001)
+    ///     .build();
+    ///
+    /// assert_eq!(error.jsonrpc_code(), 1001);
+    /// ```
+    pub fn jsonrpc_code(mut self, code: i64) -> Self {
+        self.extras_mut().jsonrpc_code = Some(code);
+        self
+    }
+}
+
+impl ApiError {
+    /// Returns the JSON-RPC error code of this error.
+    ///
+    /// A code set with [`ApiErrorBuilder::jsonrpc_code`] is used as is. Otherwise the code
+    /// is derived from the status code:
+    ///
+    /// | Status code                 | JSON-RPC code                              |
+    /// | --------------------------- | ------------------------------------------ |
+    /// | 400 Bad Request             | `-32602` Invalid params                    |
+    /// | 422 Unprocessable Entity    | `-32602` Invalid params                    |
+    /// | 404 Not Found               | `-32601` Method not found                  |
+    /// | 500 Internal Server Error   | `-32603` Internal error                    |
+    /// | Any other 4xx               | `-32000 - (status - 400)`, e.g. `-32003`   |
+    /// | Any other status            | `-32000` Server error                      |
+    ///
+    /// The other client errors fall in the `-32001` to `-32099` range that JSON-RPC
+    /// reserves for implementation-defined server errors.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_anyhow::{bad_request, forbidden, not_found};
+    ///
+    /// assert_eq!(bad_request("Invalid Params", "Expected an ID").jsonrpc_code(), -32602);
+    /// assert_eq!(not_found("Method Not Found", "No such method").jsonrpc_code(), -32601);
+    /// assert_eq!(forbidden("Forbidden", "Not your account").jsonrpc_code(), -32003);
+    /// ```
+    pub fn jsonrpc_code(&self) -> i64 {
+        if let Some(code) = self.extras().and_then(|extras| extras.jsonrpc_code) {
+            return code;
+        }
+        match self.status() {
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => INVALID_PARAMS,
+            StatusCode::NOT_FOUND => METHOD_NOT_FOUND,
+            StatusCode::INTERNAL_SERVER_ERROR => INTERNAL_ERROR,
+            status if status.is_client_error() => SERVER_ERROR - i64::from(status.as_u16() - 400),
+            _ => SERVER_ERROR,
+        }
+    }
+}
+
+/// A JSON-RPC 2.0 request, as passed to the handler of [`json_rpc`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcRequest {
+    /// The protocol version, which must be `"2.0"`
+    pub jsonrpc: String,
+    /// The name of the method to call
+    pub method: String,
+    /// The parameters of the call, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+    /// The id of the call, or `None` for a notification
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub id: Option<Value>,
+}
+
+impl JsonRpcRequest {
+    /// Deserializes the parameters of the call.
+    ///
+    /// Fails with a `400 Bad Request` error, which becomes a `-32602` Invalid params
+    /// error, if the parameters do not match `T`.
+    pub fn params<T: DeserializeOwned>(&self) -> ApiResult<T> {
+        let params = self.params.clone().unwrap_or(Value::Null);
+        serde_json::from_value(params).map_err(|err| {
+            ApiError::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .title("Invalid Params")
+                .detail(err.to_string())
+                .error(err)
+                .build()
+        })
+    }
+}
+
+/// Deserializes a field that is present, even if it is `null`, as `Some`.
+///
+/// A request with `"id": null` expects a response, unlike one without an id.
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// Handles a JSON-RPC 2.0 request body by calling `handler` for each call.
+///
+/// The body may hold a single request or a batch, whose calls are handled in order.
+/// Errors returned by the handler become JSON-RPC error objects, and the response is
+/// always sent with `200 OK`, or `204 No Content` if it only held notifications. Bodies
+/// that are not valid JSON fail with `-32700` Parse error, and requests that are not
+/// valid JSON-RPC with `-32600` Invalid Request.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{body::Bytes, response::Response, routing::post, Router};
+/// use axum_anyhow::{json_rpc, not_found, ApiResult, OptionExt};
+/// use serde_json::{json, Value};
+///
+/// async fn rpc(body: Bytes) -> Response {
+///     json_rpc(&body, |call| async move {
+///         match call.method.as_str() {
+///             "users.get" => {
+///                 let id: u32 = call.params()?;
+///                 let name = (id == 1).then_some("Alice").context_not_found("User Not Found")?;
+///                 Ok(json!({ "id": id, "name": name }))
+///             }
+///             method => Err(not_found("Method Not Found", &format!("No method named '{method}'"))),
+///         }
+///     })
+///     .await
+/// }
+///
+/// let app: Router = Router::new().route("/rpc", post(rpc));
+/// ```
+pub async fn json_rpc<F, Fut, T>(body: &[u8], mut handler: F) -> Response
+where
+    F: FnMut(JsonRpcRequest) -> Fut,
+    Fut: Future<Output = ApiResult<T>>,
+    T: Serialize,
+{
+    let body = match serde_json::from_slice::<Value>(body) {
+        Ok(body) => body,
+        Err(err) => {
+            let error = ApiError::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .title("Parse Error")
+                .detail(err.to_string())
+                .jsonrpc_code(PARSE_ERROR)
+                .error(err)
+                .build();
+            return Json(error_response(&error, Value::Null)).into_response();
+        }
+    };
+
+    match body {
+        Value::Array(calls) if !calls.is_empty() => {
+            let mut responses = Vec::new();
+            for call in calls {
+                responses.extend(call_handler(call, &mut handler).await);
+            }
+            if responses.is_empty() {
+                StatusCode::NO_CONTENT.into_response()
+            } else {
+                Json(Value::Array(responses)).into_response()
+            }
+        }
+        call => match call_handler(call, &mut handler).await {
+            Some(response) => Json(response).into_response(),
+            None => StatusCode::NO_CONTENT.into_response(),
+        },
+    }
+}
+
+/// Handles a single call, returning its response unless it is a notification.
+async fn call_handler<F, Fut, T>(call: Value, handler: &mut F) -> Option<Value>
+where
+    F: FnMut(JsonRpcRequest) -> Fut,
+    Fut: Future<Output = ApiResult<T>>,
+    T: Serialize,
+{
+    let request = match serde_json::from_value::<JsonRpcRequest>(call.clone()) {
+        Ok(request) if request.jsonrpc == "2.0" => request,
+        Ok(_) => return Some(invalid_request(&call, "The jsonrpc member must be \"2.0\"")),
+        Err(err) => return Some(invalid_request(&call, &err.to_string())),
+    };
+
+    let id = request.id.clone();
+    let result = handler(request).await.and_then(|result| {
+        serde_json::to_value(result).map_err(|err| {
+            ApiError::builder()
+                .title("Internal Error")
+                .detail("The result could not be serialized")
+                .error(err)
+                .build()
+        })
+    });
+
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err(error) => error_response(&error, id),
+    })
+}
+
+fn invalid_request(call: &Value, detail: &str) -> Value {
+    let error = ApiError::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .title("Invalid Request")
+        .detail(detail)
+        .jsonrpc_code(INVALID_REQUEST)
+        .build();
+    let id = call.get("id").cloned().unwrap_or(Value::Null);
+    error_response(&error, id)
+}
+
+fn error_response(error: &ApiError, id: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "error": error_object(error), "id": id })
+}
+
+/// Builds the `error` object, with the title as the message and the other fields of the
+/// default body as the data.
+fn error_object(error: &ApiError) -> Value {
+    let mut data = Map::new();
+    data.insert("status".to_string(), error.status().as_u16().into());
+    if let Some(detail) = error.detail() {
+        data.insert("detail".to_string(), detail.into());
+    }
+    if let Some(meta) = error.meta() {
+        data.insert("meta".to_string(), meta.clone());
+    }
+    if !error.field_errors().is_empty() {
+        data.insert("errors".to_string(), json!(error.field_errors()));
+    }
+    json!({
+        "code": error.jsonrpc_code(),
+        "message": error.title(),
+        "data": data,
+    })
+}
+
+/// Builds the response document for an error without a request id.
+pub(crate) fn to_json(error: &ApiError) -> Value {
+    error_response(error, Value::Null)
+}
+
+/// Renders an `ApiError` as a JSON-RPC 2.0 error response with a `null` id.
+pub(crate) fn into_response(error: ApiError) -> Response {
+    Json(to_json(&error)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorFormat, OptionExt};
+    use http_body_util::BodyExt;
+
+    async fn rpc(body: &str) -> (StatusCode, Value) {
+        let response = json_rpc(body.as_bytes(), |call| async move {
+            match call.method.as_str() {
+                "add" => {
+                    let (a, b): (i64, i64) = call.params()?;
+                    Ok(json!(a + b))
+                }
+                "users.get" => {
+                    let id: u32 = call.params()?;
+                    (id == 1)
+                        .then(|| json!("Alice"))
+                        .context_not_found(("User Not Found", "No user with ID 2"))
+                }
+                _ => Err(ApiError::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .title("Method Not Found")
+                    .build()),
+            }
+        })
+        .await;
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    #[test]
+    fn test_status_to_code_mapping() {
+        let code = |status| ApiError::builder().status(status).build().jsonrpc_code();
+
+        assert_eq!(code(StatusCode::BAD_REQUEST), -32602);
+        assert_eq!(code(StatusCode::UNPROCESSABLE_ENTITY), -32602);
+        assert_eq!(code(StatusCode::NOT_FOUND), -32601);
+        assert_eq!(code(StatusCode::INTERNAL_SERVER_ERROR), -32603);
+        assert_eq!(code(StatusCode::UNAUTHORIZED), -32001);
+        assert_eq!(code(StatusCode::TOO_MANY_REQUESTS), -32029);
+        assert_eq!(code(StatusCode::SERVICE_UNAVAILABLE), -32000);
+    }
+
+    #[tokio::test]
+    async fn test_single_call_result_and_error() {
+        let (status, body) =
+            rpc(r#"{"jsonrpc": "2.0", "method": "add", "params": [1, 2], "id": 1}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({"jsonrpc": "2.0", "result": 3, "id": 1}));
+
+        let (status, body) =
+            rpc(r#"{"jsonrpc": "2.0", "method": "users.get", "params": 2, "id": "a"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            json!({
+                "jsonrpc": "2.0",
+                "error": {
+                    "code": -32601,
+                    "message": "User Not Found",
+                    "data": {"status": 404, "detail": "No user with ID 2"},
+                },
+                "id": "a",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_skips_notifications() {
+        let (status, body) = rpc(r#"[
+            {"jsonrpc": "2.0", "method": "add", "params": [1, 2], "id": 1},
+            {"jsonrpc": "2.0", "method": "add", "params": [3, 4]},
+            {"jsonrpc": "2.0", "method": "add", "params": "x", "id": 2},
+            {"jsonrpc": "1.0", "method": "add", "id": 3}
+        ]"#)
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        let responses = body.as_array().unwrap();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["result"], 3);
+        assert_eq!(responses[1]["error"]["code"], -32602);
+        assert_eq!(responses[2]["error"]["code"], -32600);
+        assert_eq!(responses[2]["id"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_notifications_only_and_parse_error() {
+        let (status, _) = rpc(r#"{"jsonrpc": "2.0", "method": "add", "params": [1, 2]}"#).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, body) = rpc(r#"{"jsonrpc": "2.0", "method""#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["error"]["code"], -32700);
+        assert_eq!(body["id"], Value::Null);
+
+        let (_, body) = rpc("[]").await;
+        assert_eq!(body["error"]["code"], -32600);
+    }
+
+    #[tokio::test]
+    async fn test_error_format_renders_null_id() {
+        let error = ApiError::builder()
+            .status(StatusCode::FORBIDDEN)
+            .title("Forbidden")
+            .build();
+        let response = error.into_response_as(ErrorFormat::JsonRpc);
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "jsonrpc": "2.0",
+                "error": {"code": -32003, "message": "Forbidden", "data": {"status": 403}},
+                "id": null,
+            })
+        );
+    }
+}
@@ -1,25 +1,96 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(any(feature = "msgpack", feature = "cbor", feature = "protobuf"))]
+mod binary;
+mod canonical;
+mod connect;
 mod context;
 mod error;
 mod extensions;
+mod format;
+mod gateway;
+mod google;
+#[cfg(feature = "async-graphql")]
+mod graphql;
+#[cfg(feature = "tonic")]
+mod grpc;
 mod helpers;
 mod hook;
+mod jsonapi;
+mod jsonrpc;
+mod macros;
 mod middleware;
+mod naming;
+mod negotiate;
+mod odata;
+#[cfg(any(feature = "deadpool", feature = "bb8", feature = "r2d2"))]
+mod pool;
+mod presets;
+mod proto;
+mod scim;
+mod sse;
+mod stream;
+#[cfg(feature = "tokio")]
+mod timeout;
+mod twirp;
+#[cfg(any(feature = "reqwest", feature = "hyper-client"))]
+mod upstream;
+#[cfg(any(feature = "validator", feature = "garde"))]
+mod valid;
+mod validation;
+#[cfg(feature = "ws")]
+mod ws;
+#[cfg(feature = "xml")]
+mod xml;
 
-pub use context::ApiErrorContext;
-pub use error::{is_expose_errors_enabled, set_expose_errors, ApiError, ApiErrorBuilder};
-pub use extensions::{IntoApiError, OptionExt, ResultExt};
+pub use canonical::CanonicalCode;
+pub use context::{ctx, ApiErrorContext};
+pub use error::{
+    is_expose_errors_enabled, set_expose_errors, ApiError, ApiErrorBuilder, ApiErrorResponse,
+};
+pub use extensions::{ApiResultExt, BoolExt, IntoApiError, OptionExt, ResultExt, ResultOptionExt};
+pub use format::{
+    error_format, field_error_format, set_error_format, set_field_error_format, ErrorFormat,
+    FieldErrorFormat,
+};
+pub use gateway::PropagationPolicy;
+pub use google::{
+    error_domain, set_error_domain, ErrorDetail, ErrorInfo, FieldViolation, HelpLink,
+};
+#[cfg(feature = "tonic")]
+pub use grpc::GrpcResultExt;
 pub use helpers::{
     bad_gateway, bad_request, conflict, forbidden, gateway_timeout, internal_error,
     method_not_allowed, not_found, service_unavailable, too_many_requests, unauthorized,
     unprocessable_entity,
 };
 pub use hook::on_error;
+pub use jsonapi::ErrorSource;
+pub use jsonrpc::{json_rpc, JsonRpcRequest};
 pub use middleware::ErrorInterceptorLayer;
+pub use negotiate::ErrorFormatLayer;
+#[cfg(any(feature = "deadpool", feature = "bb8", feature = "r2d2"))]
+pub use pool::{PoolResultExt, PoolStats};
+pub use scim::{scim_bad_request, scim_conflict, scim_forbidden, ScimType};
+pub use sse::SseStream;
+pub use stream::TryStreamExt;
+#[cfg(feature = "tokio")]
+pub use timeout::FutureExt;
+#[cfg(any(feature = "reqwest", feature = "hyper-client"))]
+pub use upstream::UpstreamResultExt;
+#[cfg(feature = "garde")]
+pub use valid::Garde;
+#[cfg(feature = "validator")]
+pub use valid::Valid;
+pub use validation::{FieldError, FieldErrors};
 
 use anyhow::Result;
 
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::macros::status;
+}
+
 /// A type alias for `Result<T, ApiError>`.
 ///
 /// Use this as the return type for Axum handlers to automatically convert errors
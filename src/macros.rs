@@ -0,0 +1,207 @@
+/// Creates an `ApiError` with a status code, a title, and an optional formatted detail.
+///
+/// The status code is either the name of a 4xx or 5xx `StatusCode` constant, such as
+/// `NOT_FOUND`, or any expression that evaluates to a `StatusCode`. The detail accepts
+/// the same arguments as `format!`.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum_anyhow::api_error;
+///
+/// let id = 42;
+/// let error = api_error!(NOT_FOUND, "User Not Found", "No user with ID {}", id);
+///
+/// assert_eq!(error.status(), StatusCode::NOT_FOUND);
+/// assert_eq!(error.title(), "User Not Found");
+/// assert_eq!(error.detail(), Some("No user with ID 42"));
+///
+/// let status = StatusCode::IM_A_TEAPOT;
+/// let error = api_error!(status, "I'm a teapot");
+/// assert_eq!(error.status(), StatusCode::IM_A_TEAPOT);
+/// assert_eq!(error.detail(), None);
+/// ```
+#[macro_export]
+macro_rules! api_error {
+    ($status:ident, $($args:tt)+) => {
+        $crate::api_error!(
+            {
+                #[allow(unused_imports)]
+                use $crate::__private::status::*;
+                $status
+            },
+            $($args)+
+        )
+    };
+    ($status:expr, $title:expr $(,)?) => {
+        $crate::ApiError::builder()
+            .status($status)
+            .title($title)
+            .build()
+    };
+    ($status:expr, $title:expr, $($detail:tt)+) => {
+        $crate::ApiError::builder()
+            .status($status)
+            .title($title)
+            .detail(::std::format!($($detail)+))
+            .build()
+    };
+}
+
+/// Returns early with an `ApiError`.
+///
+/// This is the `ApiError` counterpart of `anyhow::bail!` and takes the same arguments as
+/// [`api_error!`]. The error is converted with `Into`, so it also works in functions that
+/// return other error types that `ApiError` converts into.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum_anyhow::{api_bail, ApiResult};
+///
+/// fn withdraw(balance: u32, amount: u32) -> ApiResult<u32> {
+///     if amount > balance {
+///         api_bail!(CONFLICT, "Insufficient Funds", "Balance is {}, requested {}", balance, amount);
+///     }
+///     Ok(balance - amount)
+/// }
+///
+/// let error = withdraw(10, 20).unwrap_err();
+/// assert_eq!(error.status(), StatusCode::CONFLICT);
+/// assert_eq!(error.detail(), Some("Balance is 10, requested 20"));
+/// ```
+#[macro_export]
+macro_rules! api_bail {
+    ($($args:tt)+) => {
+        return ::core::result::Result::Err(::core::convert::Into::into($crate::api_error!($($args)+)))
+    };
+}
+
+/// Returns early with an `ApiError` if a condition is false.
+///
+/// This is the `ApiError` counterpart of `anyhow::ensure!`. The first argument is the
+/// condition, and the rest are the same as for [`api_error!`].
+///
+/// # Example
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum_anyhow::{api_ensure, ApiResult};
+///
+/// fn rename(name: &str, taken: &[&str]) -> ApiResult<String> {
+///     api_ensure!(!taken.contains(&name), CONFLICT, "Conflict", "Name '{name}' is taken");
+///     Ok(name.to_string())
+/// }
+///
+/// let error = rename("admin", &["admin"]).unwrap_err();
+/// assert_eq!(error.status(), StatusCode::CONFLICT);
+/// assert_eq!(error.detail(), Some("Name 'admin' is taken"));
+/// ```
+#[macro_export]
+macro_rules! api_ensure {
+    ($cond:expr, $($args:tt)+) => {
+        if !$cond {
+            $crate::api_bail!($($args)+);
+        }
+    };
+}
+
+/// The error status codes that [`api_error!`] accepts by name.
+#[doc(hidden)]
+pub mod status {
+    use axum::http::StatusCode;
+
+    pub const BAD_REQUEST: StatusCode = StatusCode::BAD_REQUEST;
+    pub const UNAUTHORIZED: StatusCode = StatusCode::UNAUTHORIZED;
+    pub const PAYMENT_REQUIRED: StatusCode = StatusCode::PAYMENT_REQUIRED;
+    pub const FORBIDDEN: StatusCode = StatusCode::FORBIDDEN;
+    pub const NOT_FOUND: StatusCode = StatusCode::NOT_FOUND;
+    pub const METHOD_NOT_ALLOWED: StatusCode = StatusCode::METHOD_NOT_ALLOWED;
+    pub const NOT_ACCEPTABLE: StatusCode = StatusCode::NOT_ACCEPTABLE;
+    pub const PROXY_AUTHENTICATION_REQUIRED: StatusCode = StatusCode::PROXY_AUTHENTICATION_REQUIRED;
+    pub const REQUEST_TIMEOUT: StatusCode = StatusCode::REQUEST_TIMEOUT;
+    pub const CONFLICT: StatusCode = StatusCode::CONFLICT;
+    pub const GONE: StatusCode = StatusCode::GONE;
+    pub const LENGTH_REQUIRED: StatusCode = StatusCode::LENGTH_REQUIRED;
+    pub const PRECONDITION_FAILED: StatusCode = StatusCode::PRECONDITION_FAILED;
+    pub const PAYLOAD_TOO_LARGE: StatusCode = StatusCode::PAYLOAD_TOO_LARGE;
+    pub const URI_TOO_LONG: StatusCode = StatusCode::URI_TOO_LONG;
+    pub const UNSUPPORTED_MEDIA_TYPE: StatusCode = StatusCode::UNSUPPORTED_MEDIA_TYPE;
+    pub const RANGE_NOT_SATISFIABLE: StatusCode = StatusCode::RANGE_NOT_SATISFIABLE;
+    pub const EXPECTATION_FAILED: StatusCode = StatusCode::EXPECTATION_FAILED;
+    pub const IM_A_TEAPOT: StatusCode = StatusCode::IM_A_TEAPOT;
+    pub const MISDIRECTED_REQUEST: StatusCode = StatusCode::MISDIRECTED_REQUEST;
+    pub const UNPROCESSABLE_ENTITY: StatusCode = StatusCode::UNPROCESSABLE_ENTITY;
+    pub const LOCKED: StatusCode = StatusCode::LOCKED;
+    pub const FAILED_DEPENDENCY: StatusCode = StatusCode::FAILED_DEPENDENCY;
+    pub const UPGRADE_REQUIRED: StatusCode = StatusCode::UPGRADE_REQUIRED;
+    pub const PRECONDITION_REQUIRED: StatusCode = StatusCode::PRECONDITION_REQUIRED;
+    pub const TOO_MANY_REQUESTS: StatusCode = StatusCode::TOO_MANY_REQUESTS;
+    pub const REQUEST_HEADER_FIELDS_TOO_LARGE: StatusCode =
+        StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE;
+    pub const UNAVAILABLE_FOR_LEGAL_REASONS: StatusCode = StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS;
+    pub const INTERNAL_SERVER_ERROR: StatusCode = StatusCode::INTERNAL_SERVER_ERROR;
+    pub const NOT_IMPLEMENTED: StatusCode = StatusCode::NOT_IMPLEMENTED;
+    pub const BAD_GATEWAY: StatusCode = StatusCode::BAD_GATEWAY;
+    pub const SERVICE_UNAVAILABLE: StatusCode = StatusCode::SERVICE_UNAVAILABLE;
+    pub const GATEWAY_TIMEOUT: StatusCode = StatusCode::GATEWAY_TIMEOUT;
+    pub const HTTP_VERSION_NOT_SUPPORTED: StatusCode = StatusCode::HTTP_VERSION_NOT_SUPPORTED;
+    pub const VARIANT_ALSO_NEGOTIATES: StatusCode = StatusCode::VARIANT_ALSO_NEGOTIATES;
+    pub const INSUFFICIENT_STORAGE: StatusCode = StatusCode::INSUFFICIENT_STORAGE;
+    pub const LOOP_DETECTED: StatusCode = StatusCode::LOOP_DETECTED;
+    pub const NOT_EXTENDED: StatusCode = StatusCode::NOT_EXTENDED;
+    pub const NETWORK_AUTHENTICATION_REQUIRED: StatusCode =
+        StatusCode::NETWORK_AUTHENTICATION_REQUIRED;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ApiResult;
+    use axum::http::StatusCode;
+
+    #[test]
+    fn test_api_error_with_inline_format_args() {
+        let id = 7;
+        let error = api_error!(NOT_FOUND, "Not Found", "No order {id}");
+
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+        assert_eq!(error.detail(), Some("No order 7"));
+    }
+
+    #[test]
+    fn test_api_error_with_status_expression() {
+        let status = StatusCode::from_u16(418).unwrap();
+        let error = api_error!(status, String::from("Teapot"), "{} cups", 2);
+
+        assert_eq!(error.status(), StatusCode::IM_A_TEAPOT);
+        assert_eq!(error.title(), "Teapot");
+        assert_eq!(error.detail(), Some("2 cups"));
+    }
+
+    #[test]
+    fn test_api_bail_returns_error() {
+        fn handler() -> ApiResult<()> {
+            api_bail!(FORBIDDEN, "Forbidden");
+        }
+
+        let error = handler().unwrap_err();
+        assert_eq!(error.status(), StatusCode::FORBIDDEN);
+        assert_eq!(error.detail(), None);
+    }
+
+    #[test]
+    fn test_api_ensure_passes_and_fails() {
+        fn handler(admin: bool) -> ApiResult<&'static str> {
+            api_ensure!(admin, FORBIDDEN, "Forbidden", "Admin access required");
+            Ok("ok")
+        }
+
+        assert_eq!(handler(true).unwrap(), "ok");
+        assert_eq!(
+            handler(false).unwrap_err().detail(),
+            Some("Admin access required")
+        );
+    }
+}
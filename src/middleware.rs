@@ -12,6 +12,7 @@ use axum::{
 use futures_util::future::BoxFuture;
 use std::{
     cell::RefCell,
+    future::{poll_fn, Future},
     sync::Arc,
     task::{Context, Poll},
 };
@@ -83,6 +84,10 @@ impl EnrichmentContext {
     }
 
     /// Installs this enrichment context as the current thread-local data.
+    ///
+    /// Requests install their context with [`scope`](Self::scope) instead, so this is
+    /// only used by tests.
+    #[cfg(test)]
     fn set(self) {
         ENRICHMENT_CONTEXT.with(|data| {
             *data.borrow_mut() = Some(self);
@@ -90,6 +95,7 @@ impl EnrichmentContext {
     }
 
     /// Removes the current thread-local enrichment context.
+    #[cfg(test)]
     fn clear() {
         ENRICHMENT_CONTEXT.with(|data| {
             *data.borrow_mut() = None;
@@ -101,6 +107,32 @@ impl EnrichmentContext {
         (self.enricher)(builder, &self.request)
     }
 
+    /// Returns the enrichment context of the request being handled, if any.
+    pub(crate) fn current() -> Option<Self> {
+        ENRICHMENT_CONTEXT.with(|data| data.borrow().clone())
+    }
+
+    /// Runs `f` with the given enrichment context installed, restoring the previous one
+    /// afterwards, even if `f` panics.
+    ///
+    /// This lets errors built after a handler has returned, such as while a streamed
+    /// response is polled, still be enriched with the request that started the stream.
+    /// The context is moved in and out of the thread-local rather than cloned, since this
+    /// runs on every poll.
+    pub(crate) fn scope<R>(ctx: &mut Option<Self>, f: impl FnOnce() -> R) -> R {
+        if ctx.is_none() {
+            return f();
+        }
+        let previous = ENRICHMENT_CONTEXT.with(|data| data.replace(ctx.take()));
+        let _guard = ScopeGuard { ctx, previous };
+        f()
+    }
+
+    /// Calls `f` with the request being handled, if an `ErrorInterceptorLayer` is active.
+    pub(crate) fn with_request<R>(f: impl FnOnce(&RequestSnapshot) -> R) -> Option<R> {
+        ENRICHMENT_CONTEXT.with(|data| data.borrow().as_ref().map(|ctx| f(&ctx.request)))
+    }
+
     /// Invokes the error enricher if one is set and request context is available.
     ///
     /// This is called internally by `ApiErrorBuilder::build()`.
@@ -115,6 +147,19 @@ impl EnrichmentContext {
     }
 }
 
+/// Moves the scoped enrichment context back out of the thread-local when dropped, so that
+/// a panicking `f` does not leave it installed for the next request on this thread.
+struct ScopeGuard<'a> {
+    ctx: &'a mut Option<EnrichmentContext>,
+    previous: Option<EnrichmentContext>,
+}
+
+impl Drop for ScopeGuard<'_> {
+    fn drop(&mut self) {
+        *self.ctx = ENRICHMENT_CONTEXT.with(|data| data.replace(self.previous.take()));
+    }
+}
+
 /// Service that captures request context and makes it available for error enrichment.
 pub struct ErrorInterceptor<S> {
     inner: S,
@@ -149,22 +194,16 @@ where
     fn call(&mut self, request: Request) -> Self::Future {
         // Capture request context
         let snapshot = RequestSnapshot::from_request(&request);
-        let ctx = EnrichmentContext::new(snapshot, self.enricher.clone());
-
-        let future = self.inner.call(request);
+        let mut ctx = Some(EnrichmentContext::new(snapshot, self.enricher.clone()));
 
-        Box::pin(async move {
-            // Install enrichment context for this task
-            ctx.set();
+        let mut future = Box::pin(self.inner.call(request));
 
-            // Call the inner service
-            let result = future.await;
-
-            // Remove enrichment context after request completes
-            EnrichmentContext::clear();
-
-            result
-        })
+        // The context is installed for each poll and removed again before it returns.
+        // Between polls, the task may resume on another worker thread, and other requests
+        // may run on this one.
+        Box::pin(poll_fn(move |cx| {
+            EnrichmentContext::scope(&mut ctx, || future.as_mut().poll(cx))
+        }))
     }
 }
 
@@ -173,6 +212,11 @@ where
 /// This layer captures request information (method, URI, headers) and makes it available
 /// to the error enricher callback.
 ///
+/// The request context is only installed while the request future is being polled. On a
+/// multi-threaded runtime, errors built after an `.await` are therefore still enriched with
+/// their own request, even if the task has moved to another worker thread, and a request
+/// that is waiting does not leave its context behind for other requests on the thread.
+///
 /// # Example
 ///
 /// ```rust
@@ -315,4 +359,84 @@ mod tests {
             assert!(data.borrow().is_none());
         });
     }
+
+    #[test]
+    #[serial]
+    fn test_scope_restores_context_after_panic() {
+        EnrichmentContext::clear();
+        let snapshot = RequestSnapshot {
+            method: Method::GET,
+            uri: "/panics".parse().unwrap(),
+            headers: HeaderMap::default(),
+        };
+        let enricher = Arc::new(|builder: ApiErrorBuilder, _req: &RequestSnapshot| builder);
+        let mut ctx = Some(EnrichmentContext::new(snapshot, enricher));
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            EnrichmentContext::scope(&mut ctx, || panic!("handler panicked"))
+        }));
+
+        assert!(result.is_err());
+        assert!(ctx.is_some());
+        assert!(EnrichmentContext::current().is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_context_follows_request_across_threads() {
+        use crate::not_found;
+        use axum::{body::Body, http::Uri, response::IntoResponse, routing::get, Router};
+        use http_body_util::BodyExt;
+        use serde_json::Value;
+        use tower::ServiceExt;
+
+        async fn handler(uri: Uri) -> Response {
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+            not_found("Not Found", uri.path()).into_response()
+        }
+
+        let app =
+            Router::new()
+                .route("/users/{id}", get(handler))
+                .layer(ErrorInterceptorLayer::new(|builder, req| {
+                    builder.meta(json!({ "uri": req.uri().path() }))
+                }));
+
+        let requests = (0..64).map(|id| {
+            let app = app.clone();
+            tokio::spawn(async move {
+                let path = format!("/users/{id}");
+                let request = Request::get(&path).body(Body::empty()).unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let bytes = response.into_body().collect().await.unwrap().to_bytes();
+                let body: Value = serde_json::from_slice(&bytes).unwrap();
+                (path, body)
+            })
+        });
+
+        for request in requests.collect::<Vec<_>>() {
+            let (path, body) = request.await.unwrap();
+            assert_eq!(body["detail"], path.as_str());
+            assert_eq!(body["meta"]["uri"], path.as_str());
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_pending_request_leaves_no_context_on_thread() {
+        use axum::body::Body;
+        use std::task::Waker;
+
+        EnrichmentContext::clear();
+        let inner = tower::service_fn(|_: Request| async {
+            std::future::pending::<Result<Response, std::convert::Infallible>>().await
+        });
+        let mut service = ErrorInterceptorLayer::new(|builder, _| builder).layer(inner);
+        let mut future = service.call(Request::get("/slow").body(Body::empty()).unwrap());
+
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert!(EnrichmentContext::current().is_none());
+    }
 }
@@ -0,0 +1,77 @@
+//! Codes derived from error titles, shared by the error formats.
+
+use crate::CanonicalCode;
+
+/// Converts a title such as `"User Not Found"` to an `ErrorInfo` reason such as
+/// `"USER_NOT_FOUND"`.
+///
+/// A reason must match `[A-Z][A-Z0-9_]+[A-Z0-9]` and be at most 63 characters long. If
+/// the title does not give a valid reason, such as `"404"` or `"?"`, the name of the
+/// canonical code is used instead.
+pub(crate) fn reason(title: &str, fallback: CanonicalCode) -> String {
+    let reason = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_uppercase())
+        .collect::<Vec<_>>()
+        .join("_")
+        .chars()
+        .take(63)
+        .collect::<String>();
+    let reason = reason.trim_end_matches('_');
+
+    if reason.len() >= 3 && reason.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return reason.to_string();
+    }
+    match fallback {
+        CanonicalCode::Ok => CanonicalCode::Unknown,
+        code => code,
+    }
+    .as_str()
+    .to_string()
+}
+
+/// Converts a title such as `"User Not Found"` to a PascalCase code such as
+/// `"UserNotFound"`, as used by the S3 and OData formats.
+pub(crate) fn pascal_case(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reason_from_title() {
+        let reason = |title| reason(title, CanonicalCode::NotFound);
+        assert_eq!(reason("User Not Found"), "USER_NOT_FOUND");
+        assert_eq!(reason("I'm a teapot!"), "I_M_A_TEAPOT");
+    }
+
+    #[test]
+    fn test_reason_falls_back_to_canonical_code() {
+        assert_eq!(reason("", CanonicalCode::NotFound), "NOT_FOUND");
+        assert_eq!(reason("?!", CanonicalCode::Internal), "INTERNAL");
+        assert_eq!(reason("404 Error", CanonicalCode::NotFound), "NOT_FOUND");
+        assert_eq!(reason("OK", CanonicalCode::Ok), "UNKNOWN");
+
+        let long = format!("{} B", "A".repeat(62));
+        assert_eq!(reason(&long, CanonicalCode::Internal), "A".repeat(62));
+    }
+
+    #[test]
+    fn test_pascal_case_from_title() {
+        assert_eq!(pascal_case("User Not Found"), "UserNotFound");
+        assert_eq!(pascal_case("I'm a teapot!"), "IMATeapot");
+    }
+}
@@ -0,0 +1,233 @@
+//! Choosing the error format per router and per request.
+//!
+//! [`set_error_format`](crate::set_error_format) applies to the whole application.
+//! [`ErrorFormatLayer`] overrides it for the routes it wraps, and can pick the format
+//! from the `Accept` header of each request.
+
+use crate::{format::with_error_format, ErrorFormat};
+use axum::{
+    extract::Request,
+    http::{header, HeaderMap},
+    response::Response,
+};
+use futures_util::future::BoxFuture;
+use std::{
+    future::{poll_fn, Future},
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+
+/// Middleware layer that sets the error format for the routes it wraps.
+///
+/// Every `ApiError` rendered while a request is handled, including extractor
+/// rejections converted to `ApiError`, uses the format of the layer instead of the one
+/// set with [`set_error_format`](crate::set_error_format). With
+/// [`accept`](ErrorFormatLayer::accept), the format is chosen by content negotiation:
+/// the media types in the `Accept` header are tried in order of preference, and the
+/// default format is used when none of them is registered.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use axum_anyhow::{ErrorFormat, ErrorFormatLayer};
+///
+/// async fn handler() {}
+///
+/// let rpc: Router = Router::new()
+///     .route("/twirp/users", get(handler))
+///     .layer(ErrorFormatLayer::new(ErrorFormat::Twirp));
+///
+/// let api: Router = Router::new()
+///     .route("/articles", get(handler))
+///     .layer(
+///         ErrorFormatLayer::new(ErrorFormat::Problem)
+///             .accept("application/vnd.api+json", ErrorFormat::JsonApi),
+///     );
+/// ```
+#[derive(Clone, Debug)]
+pub struct ErrorFormatLayer {
+    negotiation: Arc<Negotiation>,
+}
+
+#[derive(Clone, Debug)]
+struct Negotiation {
+    default: ErrorFormat,
+    media_types: Vec<(String, ErrorFormat)>,
+}
+
+impl ErrorFormatLayer {
+    /// Creates a layer that renders errors in the given format.
+    pub fn new(format: ErrorFormat) -> Self {
+        Self {
+            negotiation: Arc::new(Negotiation {
+                default: format,
+                media_types: Vec::new(),
+            }),
+        }
+    }
+
+    /// Renders errors in `format` for requests that accept `media_type`, such as
+    /// `"application/problem+xml"`.
+    pub fn accept(mut self, media_type: impl Into<String>, format: ErrorFormat) -> Self {
+        Arc::make_mut(&mut self.negotiation)
+            .media_types
+            .push((media_type.into().to_ascii_lowercase(), format));
+        self
+    }
+}
+
+/// Service that renders errors in the format chosen by an [`ErrorFormatLayer`].
+#[derive(Clone)]
+pub struct ErrorFormatService<S> {
+    inner: S,
+    negotiation: Arc<Negotiation>,
+}
+
+impl<S> Service<Request> for ErrorFormatService<S>
+where
+    S: Service<Request, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let format = self.negotiation.format(request.headers());
+        let mut future = Box::pin(self.inner.call(request));
+
+        // The format is installed around every poll, since the task may move between
+        // threads at each await point.
+        Box::pin(poll_fn(move |cx| {
+            with_error_format(format, || future.as_mut().poll(cx))
+        }))
+    }
+}
+
+impl<S> Layer<S> for ErrorFormatLayer {
+    type Service = ErrorFormatService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ErrorFormatService {
+            inner,
+            negotiation: self.negotiation.clone(),
+        }
+    }
+}
+
+impl Negotiation {
+    /// Picks the format for a request from its `Accept` header.
+    fn format(&self, headers: &HeaderMap) -> ErrorFormat {
+        if self.media_types.is_empty() {
+            return self.default;
+        }
+
+        let mut accepted = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(media_range)
+            .collect::<Vec<_>>();
+        accepted.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        accepted
+            .iter()
+            .filter(|(_, quality)| *quality > 0.0)
+            .find_map(|(media_type, _)| {
+                self.media_types
+                    .iter()
+                    .find(|(registered, _)| registered == media_type)
+                    .map(|(_, format)| *format)
+            })
+            .unwrap_or(self.default)
+    }
+}
+
+/// Parses one media range of an `Accept` header into its media type and quality.
+fn media_range(range: &str) -> Option<(String, f32)> {
+    let mut parts = range.split(';').map(str::trim);
+    let media_type = parts.next().filter(|media_type| !media_type.is_empty())?;
+    let quality = parts
+        .filter_map(|param| param.strip_prefix("q="))
+        .find_map(|quality| quality.parse().ok())
+        .unwrap_or(1.0);
+    Some((media_type.to_ascii_lowercase(), quality))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error_format, not_found, ApiResult};
+    use axum::{body::Body, http::StatusCode, routing::get, Router};
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    fn format(layer: &ErrorFormatLayer, accept: &str) -> ErrorFormat {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, accept.parse().unwrap());
+        layer.negotiation.format(&headers)
+    }
+
+    #[test]
+    fn test_accept_header_negotiation() {
+        let layer = ErrorFormatLayer::new(ErrorFormat::Problem)
+            .accept("application/vnd.api+json", ErrorFormat::JsonApi)
+            .accept("application/x-twirp", ErrorFormat::Twirp);
+
+        assert_eq!(format(&layer, "application/json"), ErrorFormat::Problem);
+        assert_eq!(
+            format(&layer, "application/vnd.api+json"),
+            ErrorFormat::JsonApi
+        );
+        assert_eq!(
+            format(
+                &layer,
+                "application/vnd.api+json;q=0.5, application/x-twirp"
+            ),
+            ErrorFormat::Twirp
+        );
+        assert_eq!(
+            format(&layer, "Application/X-Twirp;q=0, */*"),
+            ErrorFormat::Problem
+        );
+    }
+
+    #[tokio::test]
+    async fn test_layer_sets_format_for_router() {
+        async fn handler() -> ApiResult<()> {
+            assert_eq!(error_format(), ErrorFormat::Twirp);
+            Err(not_found("User Not Found", "No user with ID 42"))
+        }
+
+        let app = Router::new()
+            .route("/", get(handler))
+            .layer(ErrorFormatLayer::new(ErrorFormat::Twirp));
+        let response = app
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["code"], json!("not_found"));
+    }
+
+    #[test]
+    fn test_format_restored_after_panic() {
+        let result = std::panic::catch_unwind(|| {
+            crate::format::with_error_format(ErrorFormat::Twirp, || panic!("handler panicked"))
+        });
+
+        assert!(result.is_err());
+        assert_ne!(error_format(), ErrorFormat::Twirp);
+    }
+}
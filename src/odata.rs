@@ -0,0 +1,180 @@
+//! [OData](https://docs.oasis-open.org/odata/odata-json-format/v4.01/odata-json-format-v4.01.html#sec_ErrorResponse)
+//! error format, as described in the
+//! [Microsoft REST API Guidelines](https://github.com/microsoft/api-guidelines/blob/vNext/azure/Guidelines.md#handling-errors).
+//!
+//! Errors are rendered as an `error` object, with one entry in `details` per field
+//! error:
+//!
+//! ```json
+//! {
+//!   "error": {
+//!     "code": "InvalidUser",
+//!     "message": "Invalid User: The request body is invalid",
+//!     "details": [
+//!       { "code": "required", "message": "Required", "target": "/email" }
+//!     ]
+//!   }
+//! }
+//! ```
+//!
+//! When error details are exposed with [`set_expose_errors`](crate::set_expose_errors),
+//! the chain of the underlying error is added as nested `innererror` objects.
+
+use crate::{is_expose_errors_enabled, naming::pascal_case, ApiError, ErrorSource};
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Map, Value};
+
+/// Renders an `ApiError` as an OData error response.
+pub(crate) fn into_response(error: ApiError) -> Response {
+    let status = error.status();
+    (status, Json(to_json(&error))).into_response()
+}
+
+/// Builds the OData error document.
+///
+/// The `code` is the code of the error, or its title in PascalCase. The `target` is the
+/// source of the error, or the pointer of its field error if it has exactly one.
+pub(crate) fn to_json(error: &ApiError) -> Value {
+    let mut object = Map::new();
+    object.insert(
+        "code".to_string(),
+        json!(error
+            .code()
+            .map_or_else(|| pascal_case(error.title()), str::to_string)),
+    );
+    object.insert("message".to_string(), json!(error.message()));
+    if let Some(target) = target(error) {
+        object.insert("target".to_string(), json!(target));
+    }
+    if !error.field_errors().is_empty() {
+        let details = error
+            .field_errors()
+            .iter()
+            .map(|field_error| {
+                json!({
+                    "code": field_error.code.as_deref().unwrap_or("invalid"),
+                    "message": field_error.message,
+                    "target": field_error.pointer,
+                })
+            })
+            .collect();
+        object.insert("details".to_string(), Value::Array(details));
+    }
+    if let Some(inner) = error.error().filter(|_| is_expose_errors_enabled()) {
+        let inner_error = inner.chain().rev().fold(None, |inner_error, cause| {
+            let mut object = Map::new();
+            object.insert("message".to_string(), json!(cause.to_string()));
+            if let Some(inner_error) = inner_error {
+                object.insert("innererror".to_string(), inner_error);
+            }
+            Some(Value::Object(object))
+        });
+        if let Some(inner_error) = inner_error {
+            object.insert("innererror".to_string(), inner_error);
+        }
+    }
+    json!({ "error": object })
+}
+
+/// Returns the target of the error.
+fn target(error: &ApiError) -> Option<&str> {
+    match error.source() {
+        Some(
+            ErrorSource::Pointer(target)
+            | ErrorSource::Parameter(target)
+            | ErrorSource::Header(target),
+        ) => Some(target),
+        None => match error.field_errors() {
+            [field_error] => Some(&field_error.pointer),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_expose_errors, ErrorFormat, FieldError};
+    use anyhow::anyhow;
+    use axum::http::StatusCode;
+    use http_body_util::BodyExt;
+    use serial_test::serial;
+
+    #[tokio::test]
+    #[serial]
+    async fn test_odata_body() {
+        set_expose_errors(false);
+        let error = ApiError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("Invalid User")
+            .detail("The request body is invalid")
+            .field_error(FieldError::new("/email", "Required").code("required"))
+            .field_error(FieldError::new("/age", "Must be a number"))
+            .error(anyhow!("validation failed"))
+            .build();
+
+        let response = error.into_response_as(ErrorFormat::OData);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(
+            body,
+            json!({
+                "error": {
+                    "code": "InvalidUser",
+                    "message": "Invalid User: The request body is invalid",
+                    "details": [
+                        {"code": "required", "message": "Required", "target": "/email"},
+                        {"code": "invalid", "message": "Must be a number", "target": "/age"},
+                    ],
+                }
+            })
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_odata_target_and_code() {
+        let error = ApiError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Not Found")
+            .code("ResourceNotFound")
+            .source_parameter("id")
+            .build();
+        assert_eq!(
+            to_json(&error),
+            json!({"error": {"code": "ResourceNotFound", "message": "Not Found", "target": "id"}})
+        );
+
+        let error = ApiError::builder()
+            .title("Invalid Order")
+            .field_error(FieldError::new("/quantity", "Required"))
+            .build();
+        assert_eq!(to_json(&error)["error"]["target"], "/quantity");
+    }
+
+    #[test]
+    #[serial]
+    fn test_odata_innererror_when_exposed() {
+        let error = ApiError::builder()
+            .error(anyhow!("connection refused").context("failed to load user"))
+            .build();
+
+        set_expose_errors(false);
+        assert!(to_json(&error)["error"].get("innererror").is_none());
+
+        set_expose_errors(true);
+        assert_eq!(
+            to_json(&error)["error"]["innererror"],
+            json!({
+                "message": "failed to load user",
+                "innererror": {"message": "connection refused"},
+            })
+        );
+        set_expose_errors(false);
+    }
+}
@@ -0,0 +1,298 @@
+//! Mapping of connection pool errors to `503 Service Unavailable`.
+//!
+//! When a database or cache pool is exhausted, the request should be retried later
+//! rather than reported as a server bug. This module recognizes the timeout and
+//! connection creation errors of `deadpool`, `bb8` and `r2d2` and turns them into
+//! `service_unavailable` errors with a `Retry-After` hint.
+
+use crate::{ApiError, ApiErrorContext, ApiResult};
+use axum::http::StatusCode;
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// The `Retry-After` delay sent with errors caused by pool exhaustion.
+const POOL_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// A snapshot of connection pool statistics.
+///
+/// Passed to [`PoolResultExt::context_pool_stats`], the statistics are attached to the
+/// error metadata under the `pool` key, where enrichers can read them through
+/// [`ApiErrorBuilder::get_meta`](crate::ApiErrorBuilder::get_meta).
+///
+/// Convert from `deadpool::Status`, `bb8::State` or `r2d2::State` with `.into()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PoolStats {
+    /// The number of connections currently managed by the pool
+    pub size: usize,
+    /// The number of idle connections
+    pub idle: usize,
+    /// The maximum number of connections, if known from the snapshot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<usize>,
+    /// The number of callers waiting for a connection, if known from the snapshot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waiting: Option<usize>,
+}
+
+#[cfg(feature = "deadpool")]
+impl From<deadpool::Status> for PoolStats {
+    fn from(status: deadpool::Status) -> Self {
+        Self {
+            size: status.size,
+            idle: status.available,
+            max_size: Some(status.max_size),
+            waiting: Some(status.waiting),
+        }
+    }
+}
+
+#[cfg(feature = "bb8")]
+impl From<bb8::State> for PoolStats {
+    fn from(state: bb8::State) -> Self {
+        Self {
+            size: state.connections as usize,
+            idle: state.idle_connections as usize,
+            max_size: None,
+            waiting: None,
+        }
+    }
+}
+
+#[cfg(feature = "r2d2")]
+impl From<r2d2::State> for PoolStats {
+    fn from(state: r2d2::State) -> Self {
+        Self {
+            size: state.connections as usize,
+            idle: state.idle_connections as usize,
+            max_size: None,
+            waiting: None,
+        }
+    }
+}
+
+/// Extension trait for results of checking a connection out of a pool.
+///
+/// Timeouts and connection creation failures become `503 Service Unavailable` with a
+/// `Retry-After` header so that load balancers back off. Any other pool error becomes a
+/// `500 Internal Server Error`.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "bb8")]
+/// # {
+/// use axum::http::{header, StatusCode};
+/// use axum_anyhow::{ApiResult, PoolResultExt};
+///
+/// fn checkout() -> Result<(), bb8::RunError<std::io::Error>> {
+///     Err(bb8::RunError::TimedOut)
+/// }
+///
+/// let result: ApiResult<()> = checkout().context_pool("Database Unavailable");
+/// let err = result.unwrap_err();
+/// assert_eq!(err.status(), StatusCode::SERVICE_UNAVAILABLE);
+/// assert_eq!(err.headers().unwrap()[header::RETRY_AFTER], "1");
+/// # }
+/// ```
+///
+/// # Sealed Trait
+///
+/// This trait is sealed and cannot be implemented for types outside this crate.
+pub trait PoolResultExt<T>: sealed::SealedPoolResult {
+    /// Converts a pool error to an `ApiError`.
+    fn context_pool(self, context: impl Into<ApiErrorContext>) -> ApiResult<T>;
+
+    /// Converts a pool error to an `ApiError`, attaching pool statistics to the metadata.
+    fn context_pool_stats(
+        self,
+        stats: impl Into<PoolStats>,
+        context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T>;
+}
+
+#[cfg(feature = "deadpool")]
+impl<E> sealed::PoolError for deadpool::managed::PoolError<E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn is_unavailable(&self) -> bool {
+        use deadpool::managed::PoolError;
+
+        matches!(
+            self,
+            PoolError::Timeout(_) | PoolError::Backend(_) | PoolError::Closed
+        )
+    }
+}
+
+#[cfg(feature = "bb8")]
+impl<E> sealed::PoolError for bb8::RunError<E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn is_unavailable(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "r2d2")]
+impl sealed::PoolError for r2d2::Error {
+    fn is_unavailable(&self) -> bool {
+        true
+    }
+}
+
+fn pool_error(
+    err: impl sealed::PoolError,
+    stats: Option<PoolStats>,
+    context: impl Into<ApiErrorContext>,
+) -> ApiError {
+    let mut builder = ApiError::builder();
+    if err.is_unavailable() {
+        builder = builder
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .retry_after(POOL_RETRY_AFTER);
+    } else {
+        builder = builder.status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    if let Some(stats) = stats {
+        builder = builder.meta(json!({ "pool": stats }));
+    }
+    context.into().apply(builder).error(err).build()
+}
+
+impl<T, E> PoolResultExt<T> for Result<T, E>
+where
+    E: sealed::PoolError,
+{
+    fn context_pool(self, context: impl Into<ApiErrorContext>) -> ApiResult<T> {
+        self.map_err(|err| pool_error(err, None, context))
+    }
+
+    fn context_pool_stats(
+        self,
+        stats: impl Into<PoolStats>,
+        context: impl Into<ApiErrorContext>,
+    ) -> ApiResult<T> {
+        self.map_err(|err| pool_error(err, Some(stats.into()), context))
+    }
+}
+
+mod sealed {
+    /// A pool error that knows whether it was caused by pool exhaustion.
+    pub trait PoolError: Into<anyhow::Error> {
+        fn is_unavailable(&self) -> bool;
+    }
+
+    pub trait SealedPoolResult {}
+
+    impl<T, E> SealedPoolResult for Result<T, E> where E: PoolError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header;
+
+    #[cfg(feature = "deadpool")]
+    #[test]
+    fn test_deadpool_timeout_is_service_unavailable() {
+        use deadpool::managed::{PoolError, TimeoutType};
+
+        let result: Result<(), PoolError<std::io::Error>> =
+            Err(PoolError::Timeout(TimeoutType::Wait));
+        let err = result
+            .context_pool(("Database Unavailable", "No connection available"))
+            .unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(err.title(), "Database Unavailable");
+        assert_eq!(err.detail(), Some("No connection available"));
+        assert_eq!(err.headers().unwrap()[header::RETRY_AFTER], "1");
+    }
+
+    #[cfg(feature = "deadpool")]
+    #[test]
+    fn test_deadpool_no_runtime_is_internal() {
+        use deadpool::managed::PoolError;
+
+        let result: Result<(), PoolError<std::io::Error>> = Err(PoolError::NoRuntimeSpecified);
+        let err = result.context_pool("Database Error").unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(err.headers().is_none());
+    }
+
+    #[cfg(feature = "deadpool")]
+    #[test]
+    fn test_deadpool_stats_in_meta() {
+        use deadpool::managed::{PoolError, TimeoutType};
+
+        let status = deadpool::Status {
+            max_size: 10,
+            size: 10,
+            available: 0,
+            waiting: 3,
+        };
+        let result: Result<(), PoolError<std::io::Error>> =
+            Err(PoolError::Timeout(TimeoutType::Wait));
+        let err = result
+            .context_pool_stats(status, "Database Unavailable")
+            .unwrap_err();
+
+        let meta = err.meta().unwrap();
+        assert_eq!(meta["pool"]["max_size"], 10);
+        assert_eq!(meta["pool"]["size"], 10);
+        assert_eq!(meta["pool"]["idle"], 0);
+        assert_eq!(meta["pool"]["waiting"], 3);
+    }
+
+    #[cfg(feature = "bb8")]
+    #[test]
+    fn test_bb8_creation_error_is_service_unavailable() {
+        let result: Result<(), bb8::RunError<std::io::Error>> = Err(bb8::RunError::User(
+            std::io::Error::other("connection refused"),
+        ));
+        let err = result.context_pool("Database Unavailable").unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(err.headers().unwrap()[header::RETRY_AFTER], "1");
+        assert!(err.meta().is_none());
+    }
+
+    #[cfg(feature = "r2d2")]
+    #[test]
+    fn test_r2d2_timeout_is_service_unavailable() {
+        struct FailingManager;
+
+        impl r2d2::ManageConnection for FailingManager {
+            type Connection = ();
+            type Error = std::io::Error;
+
+            fn connect(&self) -> Result<(), std::io::Error> {
+                Err(std::io::Error::other("connection refused"))
+            }
+
+            fn is_valid(&self, _conn: &mut ()) -> Result<(), std::io::Error> {
+                Ok(())
+            }
+
+            fn has_broken(&self, _conn: &mut ()) -> bool {
+                false
+            }
+        }
+
+        let pool = r2d2::Pool::builder()
+            .connection_timeout(Duration::from_millis(10))
+            .build_unchecked(FailingManager);
+        let err = pool
+            .get()
+            .context_pool_stats(pool.state(), "Database Unavailable")
+            .unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(err.headers().unwrap()[header::RETRY_AFTER], "1");
+        assert_eq!(err.meta().unwrap()["pool"]["size"], 0);
+    }
+}
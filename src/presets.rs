@@ -0,0 +1,362 @@
+//! Error bodies in the shapes of other web frameworks.
+//!
+//! These presets let a service be rewritten in axum without breaking clients that parse
+//! the error bodies of the framework it used before:
+//!
+//! - Spring Boot: `{"timestamp", "status", "error", "message", "path"}`, plus an
+//!   `errors` array of field errors
+//! - FastAPI: `{"detail": "..."}`, or `{"detail": [{"loc", "msg", "type"}]}` for field
+//!   errors
+//! - Django REST Framework: `{"detail": "..."}`, or a map from field names to lists of
+//!   messages for field errors
+//!
+//! Each of these frameworks has a single message per error, which is the detail of the
+//! error if it has one and its title otherwise. The path of the Spring Boot body is read
+//! from the request captured by an [`ErrorInterceptorLayer`](crate::ErrorInterceptorLayer),
+//! and is omitted without one.
+
+use crate::{middleware::EnrichmentContext, ApiError};
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Map, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Renders an `ApiError` as a Spring Boot error response.
+pub(crate) fn into_spring_response(error: ApiError) -> Response {
+    let status = error.status();
+    (status, Json(spring_json(&error))).into_response()
+}
+
+/// Renders an `ApiError` as a FastAPI error response.
+pub(crate) fn into_fastapi_response(error: ApiError) -> Response {
+    let status = error.status();
+    (status, Json(fastapi_json(&error))).into_response()
+}
+
+/// Renders an `ApiError` as a Django REST Framework error response.
+pub(crate) fn into_drf_response(error: ApiError) -> Response {
+    let status = error.status();
+    (status, Json(drf_json(&error))).into_response()
+}
+
+/// Builds the body of Spring Boot's `DefaultErrorAttributes`.
+///
+/// Field errors are listed under `errors` like Spring's binding errors, with the JSON
+/// Pointer converted to a property path such as `address.city`.
+pub(crate) fn spring_json(error: &ApiError) -> Value {
+    let mut body = Map::new();
+    body.insert("timestamp".to_string(), json!(timestamp(SystemTime::now())));
+    body.insert("status".to_string(), json!(error.status().as_u16()));
+    body.insert(
+        "error".to_string(),
+        json!(error.status().canonical_reason().unwrap_or("Unknown")),
+    );
+    body.insert("message".to_string(), json!(message(error)));
+    if !error.field_errors().is_empty() {
+        let errors = error
+            .field_errors()
+            .iter()
+            .map(|field_error| {
+                let mut object = Map::new();
+                object.insert("field".to_string(), json!(field_error.segments().join(".")));
+                object.insert("defaultMessage".to_string(), json!(field_error.message));
+                if let Some(code) = &field_error.code {
+                    object.insert("code".to_string(), json!(code));
+                }
+                Value::Object(object)
+            })
+            .collect();
+        body.insert("errors".to_string(), Value::Array(errors));
+    }
+    if let Some(path) = EnrichmentContext::with_request(|request| request.uri().path().to_string())
+    {
+        body.insert("path".to_string(), json!(path));
+    }
+    Value::Object(body)
+}
+
+/// Builds the body of a FastAPI `HTTPException`, or of a `RequestValidationError` if
+/// the error has field errors.
+///
+/// The `loc` of a field error starts with `body` for a JSON Pointer, including the root
+/// pointer `""`, and with `query` for a query parameter, and its `type` is the code of
+/// the field error.
+pub(crate) fn fastapi_json(error: &ApiError) -> Value {
+    if error.field_errors().is_empty() {
+        return json!({ "detail": message(error) });
+    }
+
+    let detail = error
+        .field_errors()
+        .iter()
+        .map(|field_error| {
+            let pointer = &field_error.pointer;
+            let location = if pointer.starts_with('/') || pointer.is_empty() {
+                "body"
+            } else {
+                "query"
+            };
+            let loc = std::iter::once(json!(location))
+                .chain(field_error.segments().into_iter().map(|segment| {
+                    segment
+                        .parse::<u64>()
+                        .map_or_else(|_| json!(segment), |index| json!(index))
+                }))
+                .collect::<Vec<_>>();
+            json!({
+                "loc": loc,
+                "msg": field_error.message,
+                "type": field_error.code.as_deref().unwrap_or("value_error"),
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({ "detail": detail })
+}
+
+/// The key under which DRF lists errors that do not belong to a single field.
+const NON_FIELD_ERRORS: &str = "non_field_errors";
+
+/// Builds the body of a Django REST Framework exception, or of a `ValidationError` if
+/// the error has field errors.
+///
+/// Field errors are nested by the segments of their JSON Pointer, as DRF does for nested
+/// serializers. Field errors that point at the whole body are listed under
+/// `non_field_errors`, and so are the errors of a field that also has nested errors,
+/// inside the object of that field.
+///
+/// A pointer does not say whether a segment is a list index, so list items are always
+/// keyed by their index as a string, such as `{"tags": {"0": ["Too long."]}}`. This is
+/// what DRF returns for the children of a `ListField`. For a nested serializer with
+/// `many=True`, DRF instead returns a list with one entry per item.
+pub(crate) fn drf_json(error: &ApiError) -> Value {
+    if error.field_errors().is_empty() {
+        return json!({ "detail": message(error) });
+    }
+
+    let mut body = Map::new();
+    for field_error in error.field_errors() {
+        let mut segments = field_error.segments();
+        if segments.is_empty() {
+            segments.push(NON_FIELD_ERRORS.to_string());
+        }
+        let (field, parents) = segments.split_last().expect("segments is not empty");
+        let mut object = &mut body;
+        for parent in parents {
+            let value = object
+                .entry(parent.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !value.is_object() {
+                let messages = value.take();
+                *value = Value::Object(Map::from_iter([(NON_FIELD_ERRORS.to_string(), messages)]));
+            }
+            object = value.as_object_mut().expect("value is an object");
+        }
+        let mut messages = object
+            .entry(field.clone())
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Object(nested) = messages {
+            messages = nested
+                .entry(NON_FIELD_ERRORS)
+                .or_insert_with(|| Value::Array(Vec::new()));
+        }
+        if let Value::Array(messages) = messages {
+            messages.push(json!(field_error.message));
+        }
+    }
+    Value::Object(body)
+}
+
+/// Returns the detail of the error, or its title if it has no detail.
+fn message(error: &ApiError) -> &str {
+    error.detail().unwrap_or(error.title())
+}
+
+/// Formats a time the way Spring Boot serializes timestamps, such as
+/// `"2024-05-01T12:30:00.000+00:00"`.
+fn timestamp(time: SystemTime) -> String {
+    let elapsed = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = elapsed.as_secs();
+    let (days, seconds_of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // Converts days since 1970-01-01 to a civil date, after Howard Hinnant's
+    // `civil_from_days`.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}+00:00",
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60,
+        elapsed.subsec_millis(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{not_found, ErrorFormat, ErrorInterceptorLayer, FieldError};
+    use axum::{body::Body, extract::Request, http::StatusCode, routing::get, Router};
+    use http_body_util::BodyExt;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn invalid_user() -> ApiError {
+        ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("Invalid User")
+            .field_error(FieldError::new("/age", "Must be at least 18").code("range"))
+            .field_error(FieldError::new("/address/city", "Required"))
+            .field_error(FieldError::new("/tags/0", "Too long"))
+            .field_error(FieldError::new("page", "Must be a number"))
+            .build()
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_714_566_600_042);
+        assert_eq!(timestamp(time), "2024-05-01T12:30:00.042+00:00");
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00.000+00:00");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(951_782_400);
+        assert_eq!(timestamp(leap_day), "2000-02-29T00:00:00.000+00:00");
+    }
+
+    #[tokio::test]
+    async fn test_spring_body_has_path_from_request() {
+        async fn handler() -> Response {
+            not_found("User Not Found", "No user with ID 42").into_response_as(ErrorFormat::Spring)
+        }
+
+        let app = Router::new()
+            .route("/users/{id}", get(handler))
+            .layer(ErrorInterceptorLayer::new(|builder, _| builder));
+        let response = app
+            .oneshot(Request::get("/users/42?x=1").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert!(body["timestamp"].as_str().unwrap().ends_with("+00:00"));
+        assert_eq!(body["status"], 404);
+        assert_eq!(body["error"], "Not Found");
+        assert_eq!(body["message"], "No user with ID 42");
+        assert_eq!(body["path"], "/users/42");
+    }
+
+    #[test]
+    fn test_spring_body_field_errors() {
+        let body = spring_json(&invalid_user());
+
+        assert_eq!(body["status"], 422);
+        assert_eq!(body["message"], "Invalid User");
+        assert_eq!(
+            body["errors"][0],
+            json!({"field": "age", "defaultMessage": "Must be at least 18", "code": "range"})
+        );
+        assert_eq!(body["errors"][1]["field"], "address.city");
+        assert!(body.get("path").is_none());
+    }
+
+    #[test]
+    fn test_fastapi_body() {
+        assert_eq!(
+            fastapi_json(&invalid_user()),
+            json!({
+                "detail": [
+                    {"loc": ["body", "age"], "msg": "Must be at least 18", "type": "range"},
+                    {"loc": ["body", "address", "city"], "msg": "Required", "type": "value_error"},
+                    {"loc": ["body", "tags", 0], "msg": "Too long", "type": "value_error"},
+                    {"loc": ["query", "page"], "msg": "Must be a number", "type": "value_error"},
+                ]
+            })
+        );
+
+        let error = ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("Invalid User")
+            .field_error(FieldError::new("", "Passwords do not match"))
+            .build();
+        assert_eq!(
+            fastapi_json(&error),
+            json!({
+                "detail": [
+                    {"loc": ["body"], "msg": "Passwords do not match", "type": "value_error"},
+                ]
+            })
+        );
+
+        let error = ApiError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Not Found")
+            .build();
+        assert_eq!(fastapi_json(&error), json!({"detail": "Not Found"}));
+    }
+
+    #[tokio::test]
+    async fn test_drf_body() {
+        let error = ApiError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("Invalid User")
+            .field_error(FieldError::new("/email", "Enter a valid email address."))
+            .field_error(FieldError::new("/email", "This field must be unique."))
+            .field_error(FieldError::new("/address/city", "This field is required."))
+            .field_error(FieldError::new("", "Passwords do not match."))
+            .field_error(FieldError::new(
+                "/tags",
+                "Ensure this field has no more than 3 elements.",
+            ))
+            .field_error(FieldError::new(
+                "/tags/0",
+                "Ensure this field has no more than 8 characters.",
+            ))
+            .field_error(FieldError::new("/roles/0", "Not a valid choice."))
+            .field_error(FieldError::new("/roles", "This list may not be empty."))
+            .build();
+
+        let response = error.into_response_as(ErrorFormat::Drf);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(
+            body,
+            json!({
+                "email": ["Enter a valid email address.", "This field must be unique."],
+                "address": {"city": ["This field is required."]},
+                "non_field_errors": ["Passwords do not match."],
+                "tags": {
+                    "non_field_errors": ["Ensure this field has no more than 3 elements."],
+                    "0": ["Ensure this field has no more than 8 characters."],
+                },
+                "roles": {
+                    "0": ["Not a valid choice."],
+                    "non_field_errors": ["This list may not be empty."],
+                },
+            })
+        );
+
+        let error = ApiError::builder()
+            .status(StatusCode::FORBIDDEN)
+            .title("Forbidden")
+            .detail("You do not have permission to perform this action.")
+            .build();
+        assert_eq!(
+            drf_json(&error),
+            json!({"detail": "You do not have permission to perform this action."})
+        );
+    }
+}
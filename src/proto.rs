@@ -0,0 +1,75 @@
+//! Just enough of the protobuf wire format to encode and decode error messages, without
+//! depending on a protobuf runtime.
+
+/// Writes a string field, skipping it when empty as proto3 does.
+pub(crate) fn encode_string(buf: &mut Vec<u8>, field: u64, value: &str) {
+    if !value.is_empty() {
+        encode_bytes(buf, field, value.as_bytes());
+    }
+}
+
+/// Writes a length-delimited field, such as a string or an embedded message.
+pub(crate) fn encode_bytes(buf: &mut Vec<u8>, field: u64, value: &[u8]) {
+    encode_varint(buf, field << 3 | 2);
+    encode_varint(buf, value.len() as u64);
+    buf.extend_from_slice(value);
+}
+
+/// Writes a varint field, such as an integer, a bool or an enum value.
+#[cfg(feature = "protobuf")]
+pub(crate) fn encode_uint(buf: &mut Vec<u8>, field: u64, value: u64) {
+    encode_varint(buf, field << 3);
+    encode_varint(buf, value);
+}
+
+/// Writes a `double` field.
+#[cfg(feature = "protobuf")]
+pub(crate) fn encode_double(buf: &mut Vec<u8>, field: u64, value: f64) {
+    encode_varint(buf, field << 3 | 1);
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// Decodes one field, returning its number and, for length-delimited fields, its bytes.
+pub(crate) fn decode_field<'a>(message: &mut &'a [u8]) -> Option<(u64, Option<&'a [u8]>)> {
+    let tag = decode_varint(message)?;
+    let bytes = match tag & 7 {
+        0 => {
+            decode_varint(message)?;
+            None
+        }
+        1 | 5 => {
+            let len = if tag & 7 == 1 { 8 } else { 4 };
+            *message = message.get(len..)?;
+            None
+        }
+        2 => {
+            let len = usize::try_from(decode_varint(message)?).ok()?;
+            let bytes = message.get(..len)?;
+            *message = &message[len..];
+            Some(bytes)
+        }
+        _ => return None,
+    };
+    Some((tag >> 3, bytes))
+}
+
+fn decode_varint(message: &mut &[u8]) -> Option<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = message.split_first()?;
+        *message = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return Some(value);
+        }
+    }
+    None
+}
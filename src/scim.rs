@@ -0,0 +1,288 @@
+//! [SCIM 2.0](https://www.rfc-editor.org/rfc/rfc7644#section-3.12) error responses.
+//!
+//! Errors are rendered as SCIM error messages and sent as `application/scim+json`:
+//!
+//! ```json
+//! {
+//!   "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+//!   "status": "409",
+//!   "scimType": "uniqueness",
+//!   "detail": "User Exists: userName bjensen is already taken"
+//! }
+//! ```
+
+use crate::{ApiError, ApiErrorBuilder};
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Map, Value};
+
+const SCIM_CONTENT_TYPE: &str = "application/scim+json";
+const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+/// The `scimType` of a SCIM error, as defined in
+/// [RFC 7644, Section 3.12](https://www.rfc-editor.org/rfc/rfc7644#section-3.12).
+///
+/// `Uniqueness` is sent with 409 Conflict, `Sensitive` with 403 Forbidden, and all
+/// the others with 400 Bad Request.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum_anyhow::{ApiError, ScimType};
+///
+/// let error = ApiError::builder()
+///     .status(StatusCode::BAD_REQUEST)
+///     .title("Invalid Filter")
+///     .scim_type(ScimType::InvalidFilter)
+///     .build();
+///
+/// assert_eq!(error.scim_type(), Some(ScimType::InvalidFilter));
+/// assert_eq!(ScimType::InvalidFilter.as_str(), "invalidFilter");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ScimType {
+    /// The filter syntax is invalid, or the filter has an invalid attribute or value
+    InvalidFilter,
+    /// The filter yields too many results for the server to process
+    TooMany,
+    /// One or more attribute values are already in use or reserved
+    Uniqueness,
+    /// The modification is incompatible with the mutability of the target attribute
+    Mutability,
+    /// The request body is not valid for the request or the schema
+    InvalidSyntax,
+    /// The path attribute is invalid or malformed
+    InvalidPath,
+    /// The path attribute did not yield an attribute that could be operated on
+    NoTarget,
+    /// A required value was missing, or a value is not compatible with the attribute
+    InvalidValue,
+    /// The specified SCIM protocol version is not supported
+    InvalidVers,
+    /// The request cannot be completed because it would expose sensitive information
+    Sensitive,
+}
+
+impl ScimType {
+    /// Returns the `scimType` keyword, such as `"uniqueness"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScimType::InvalidFilter => "invalidFilter",
+            ScimType::TooMany => "tooMany",
+            ScimType::Uniqueness => "uniqueness",
+            ScimType::Mutability => "mutability",
+            ScimType::InvalidSyntax => "invalidSyntax",
+            ScimType::InvalidPath => "invalidPath",
+            ScimType::NoTarget => "noTarget",
+            ScimType::InvalidValue => "invalidValue",
+            ScimType::InvalidVers => "invalidVers",
+            ScimType::Sensitive => "sensitive",
+        }
+    }
+}
+
+impl ApiErrorBuilder {
+    /// Sets the `scimType` sent with the error in the SCIM format.
+    pub fn scim_type(mut self, scim_type: ScimType) -> Self {
+        self.extras_mut().scim_type = Some(scim_type);
+        self
+    }
+}
+
+impl ApiError {
+    /// Returns the `scimType` of this error, if one was set.
+    pub fn scim_type(&self) -> Option<ScimType> {
+        self.extras().and_then(|extras| extras.scim_type)
+    }
+}
+
+/// Creates a 400 Bad Request error with a `scimType`.
+///
+/// Use [`scim_conflict`] for `Uniqueness` and [`scim_forbidden`] for `Sensitive`, which
+/// RFC 7644 sends with other status codes.
+///
+/// # Arguments
+///
+/// * `scim_type` - The kind of SCIM error, such as `InvalidFilter`
+/// * `title` - A short, human-readable summary of the error
+/// * `detail` - A detailed explanation of the error
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::{scim_bad_request, ScimType};
+/// use axum::http::StatusCode;
+///
+/// let error = scim_bad_request(ScimType::Mutability, "Read-Only Attribute", "id cannot be changed");
+/// assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+/// assert_eq!(error.scim_type(), Some(ScimType::Mutability));
+/// ```
+pub fn scim_bad_request(scim_type: ScimType, title: &str, detail: &str) -> ApiError {
+    ApiError::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .title(title)
+        .detail(detail)
+        .scim_type(scim_type)
+        .build()
+}
+
+/// Creates a 409 Conflict error with the `uniqueness` `scimType`.
+///
+/// # Arguments
+///
+/// * `title` - A short, human-readable summary of the error
+/// * `detail` - A detailed explanation of the error
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::{scim_conflict, ScimType};
+/// use axum::http::StatusCode;
+///
+/// let error = scim_conflict("User Exists", "userName bjensen is already taken");
+/// assert_eq!(error.status(), StatusCode::CONFLICT);
+/// assert_eq!(error.scim_type(), Some(ScimType::Uniqueness));
+/// ```
+pub fn scim_conflict(title: &str, detail: &str) -> ApiError {
+    ApiError::builder()
+        .status(StatusCode::CONFLICT)
+        .title(title)
+        .detail(detail)
+        .scim_type(ScimType::Uniqueness)
+        .build()
+}
+
+/// Creates a 403 Forbidden error with the `sensitive` `scimType`.
+///
+/// # Arguments
+///
+/// * `title` - A short, human-readable summary of the error
+/// * `detail` - A detailed explanation of the error
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::{scim_forbidden, ScimType};
+/// use axum::http::StatusCode;
+///
+/// let error = scim_forbidden("Sensitive Filter", "Filter on password is not allowed");
+/// assert_eq!(error.status(), StatusCode::FORBIDDEN);
+/// assert_eq!(error.scim_type(), Some(ScimType::Sensitive));
+/// ```
+pub fn scim_forbidden(title: &str, detail: &str) -> ApiError {
+    ApiError::builder()
+        .status(StatusCode::FORBIDDEN)
+        .title(title)
+        .detail(detail)
+        .scim_type(ScimType::Sensitive)
+        .build()
+}
+
+/// Renders an `ApiError` as a SCIM error response.
+pub(crate) fn into_response(error: ApiError) -> Response {
+    let status = error.status();
+    let mut response = (status, Json(to_json(&error))).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(SCIM_CONTENT_TYPE),
+    );
+    response
+}
+
+/// Builds the SCIM error message. The status is a string, as RFC 7644 requires.
+pub(crate) fn to_json(error: &ApiError) -> Value {
+    let mut body = Map::new();
+    body.insert("schemas".to_string(), json!([ERROR_SCHEMA]));
+    body.insert("status".to_string(), json!(error.status().as_str()));
+    if let Some(scim_type) = error.scim_type() {
+        body.insert("scimType".to_string(), json!(scim_type.as_str()));
+    }
+    body.insert("detail".to_string(), json!(error.message()));
+    Value::Object(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorFormat;
+    use http_body_util::BodyExt;
+
+    async fn body(error: ApiError) -> Value {
+        let response = error.into_response_as(ErrorFormat::Scim);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], SCIM_CONTENT_TYPE);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_scim_conflict() {
+        let error = scim_conflict("User Exists", "userName bjensen is already taken");
+
+        assert_eq!(
+            body(error).await,
+            json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+                "status": "409",
+                "scimType": "uniqueness",
+                "detail": "User Exists: userName bjensen is already taken",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scim_forbidden() {
+        let error = scim_forbidden("Sensitive Filter", "Filter on password is not allowed");
+
+        let body = body(error).await;
+        assert_eq!(body["status"], "403");
+        assert_eq!(body["scimType"], "sensitive");
+    }
+
+    #[tokio::test]
+    async fn test_scim_without_scim_type() {
+        let error = ApiError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Resource Not Found")
+            .build();
+
+        assert_eq!(
+            body(error).await,
+            json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+                "status": "404",
+                "detail": "Resource Not Found",
+            })
+        );
+    }
+
+    #[test]
+    fn test_scim_type_survives_recontext() {
+        use crate::ApiResultExt;
+
+        let error = Err::<(), _>(scim_bad_request(
+            ScimType::TooMany,
+            "Too Many Results",
+            "Narrow the filter",
+        ))
+        .recontext_bad_request("Search Failed")
+        .unwrap_err();
+
+        assert_eq!(error.scim_type(), Some(ScimType::TooMany));
+    }
+
+    #[test]
+    fn test_scim_type_dropped_when_recontext_changes_status() {
+        use crate::ApiResultExt;
+
+        let error = Err::<(), _>(scim_conflict("Conflict", "User name already exists"))
+            .recontext_forbidden("Forbidden")
+            .unwrap_err();
+
+        assert_eq!(error.scim_type(), None);
+    }
+}
@@ -0,0 +1,218 @@
+//! Error events for Server-Sent Events streams.
+//!
+//! Once an SSE response has started, a failure can no longer change the status code, so
+//! it has to be sent as an event. [`ApiError::into_sse_event`] renders an error as an
+//! `error` event, and [`SseStream`] does so for every failed item of a stream.
+
+use crate::{
+    format::{error_format, with_error_format},
+    middleware::EnrichmentContext,
+    ApiError, ApiResult, ErrorFormat,
+};
+use axum::response::sse::Event;
+use futures_util::Stream;
+use std::{
+    convert::Infallible,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+impl ApiError {
+    /// Converts this error into an SSE event named `error`.
+    ///
+    /// The data of the event is the JSON body that the error would have as a response,
+    /// in the current [`ErrorFormat`](crate::ErrorFormat).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_anyhow::not_found;
+    ///
+    /// let event = not_found("Not Found", "The job no longer exists").into_sse_event();
+    /// ```
+    pub fn into_sse_event(self) -> Event {
+        Event::default()
+            .event("error")
+            .json_data(self.into_json(error_format()))
+            .expect("a JSON value is always serializable")
+    }
+}
+
+/// A stream adapter for SSE endpoints that turns failed items into `error` events.
+///
+/// Wrap a stream of `ApiResult<Event>` in `SseStream` and pass it to
+/// [`Sse::new`](axum::response::sse::Sse::new). `Ok` events are sent as they are, and
+/// each error is sent with [`ApiError::into_sse_event`]. With
+/// [`close_on_error`](SseStream::close_on_error), the stream ends after the first error.
+///
+/// The stream keeps the request context of the [`ErrorInterceptorLayer`](crate::ErrorInterceptorLayer)
+/// that was active when it was created. Errors built while the stream is polled, after
+/// the handler has returned, are therefore still enriched with the originating request,
+/// and the [`on_error`](crate::on_error) hook sees them as usual. Error events are
+/// rendered in the error format that was current when the stream was created, such as
+/// the one of an [`ErrorFormatLayer`](crate::ErrorFormatLayer) around the route.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::response::sse::{Event, Sse};
+/// use axum_anyhow::{ApiResult, ResultExt, SseStream};
+/// use futures_util::{stream, Stream, StreamExt};
+/// use std::convert::Infallible;
+///
+/// fn progress() -> impl Stream<Item = anyhow::Result<u32>> {
+///     stream::iter(vec![Ok(50), Ok(100)])
+/// }
+///
+/// async fn handler() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+///     let events = progress().map(|percent| {
+///         let percent = percent.context_internal("Job Failed")?;
+///         ApiResult::Ok(Event::default().data(percent.to_string()))
+///     });
+///
+///     Sse::new(SseStream::new(events).close_on_error())
+/// }
+/// ```
+pub struct SseStream<S> {
+    stream: Pin<Box<S>>,
+    enrichment: Option<EnrichmentContext>,
+    format: ErrorFormat,
+    close_on_error: bool,
+    closed: bool,
+}
+
+impl<S> SseStream<S> {
+    /// Wraps a stream of events, capturing the request context and error format of the
+    /// current handler.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream: Box::pin(stream),
+            enrichment: EnrichmentContext::current(),
+            format: error_format(),
+            close_on_error: false,
+            closed: false,
+        }
+    }
+
+    /// Ends the stream after the first error event.
+    pub fn close_on_error(mut self) -> Self {
+        self.close_on_error = true;
+        self
+    }
+}
+
+impl<S> Stream for SseStream<S>
+where
+    S: Stream<Item = ApiResult<Event>>,
+{
+    type Item = Result<Event, Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.closed {
+            return Poll::Ready(None);
+        }
+
+        // The format is installed for the poll as well, so that it applies to errors
+        // converted by the inner stream.
+        with_error_format(this.format, || {
+            let stream = &mut this.stream;
+            let poll =
+                EnrichmentContext::scope(&mut this.enrichment, || stream.as_mut().poll_next(cx));
+
+            poll.map(|item| {
+                item.map(|result| {
+                    Ok(result.unwrap_or_else(|err| {
+                        this.closed = this.close_on_error;
+                        err.into_sse_event()
+                    }))
+                })
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorInterceptorLayer, ResultExt};
+    use anyhow::anyhow;
+    use axum::{body::Body, extract::Request, response::sse::Sse, routing::get, Router};
+    use futures_util::{stream, StreamExt};
+    use http_body_util::BodyExt;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    async fn events(close_on_error: bool) -> String {
+        let handler = move || async move {
+            let events = stream::iter(vec![Ok(1), Err(anyhow!("worker crashed")), Ok(3)]).map(
+                |item: anyhow::Result<u32>| {
+                    let item = item.context_internal("Job Failed")?;
+                    ApiResult::Ok(Event::default().data(item.to_string()))
+                },
+            );
+            let events = SseStream::new(events);
+            Sse::new(if close_on_error {
+                events.close_on_error()
+            } else {
+                events
+            })
+        };
+        let app = Router::new()
+            .route("/jobs", get(handler))
+            .layer(ErrorInterceptorLayer::new(|builder, request| {
+                builder.meta(json!({ "uri": request.uri().to_string() }))
+            }));
+
+        let response = app
+            .oneshot(Request::get("/jobs").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_errors_become_enriched_error_events() {
+        assert_eq!(
+            events(false).await,
+            "data: 1\n\n\
+             event: error\n\
+             data: {\"meta\":{\"uri\":\"/jobs\"},\"status\":500,\"title\":\"Job Failed\"}\n\n\
+             data: 3\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_events_use_format_of_route() {
+        use crate::{not_found, ErrorFormatLayer};
+
+        async fn handler() -> Sse<SseStream<impl Stream<Item = ApiResult<Event>>>> {
+            let events = stream::iter(vec![ApiResult::Err(not_found("Job Not Found", "Gone"))]);
+            Sse::new(SseStream::new(events))
+        }
+
+        let app = Router::new()
+            .route("/jobs", get(handler))
+            .layer(ErrorFormatLayer::new(ErrorFormat::Twirp));
+        let response = app
+            .oneshot(Request::get("/jobs").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+
+        assert_eq!(
+            String::from_utf8(body.to_vec()).unwrap(),
+            "event: error\n\
+             data: {\"code\":\"not_found\",\"msg\":\"Job Not Found: Gone\"}\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_close_on_error_ends_stream() {
+        let body = events(true).await;
+
+        assert!(body.ends_with("\"title\":\"Job Failed\"}\n\n"));
+        assert!(!body.contains("data: 3"));
+    }
+}
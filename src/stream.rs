@@ -0,0 +1,477 @@
+//! Error handling for streamed responses.
+//!
+//! Streaming handlers, such as NDJSON or CSV exports, produce a
+//! `Stream<Item = Result<T, E>>`. [`TryStreamExt`] converts the error of each item into
+//! an `ApiError`, and can end the stream with an error record instead of cutting the
+//! response off.
+
+use crate::{
+    format::{error_format, with_error_format},
+    middleware::EnrichmentContext,
+    ApiError, ApiErrorContext, ApiResult, ErrorFormat, IntoApiError,
+};
+use axum::http::StatusCode;
+use futures_util::{stream, Stream, StreamExt};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Extension trait for streams of results, to convert item errors into `ApiError`.
+///
+/// The `context_*` methods work like the ones on [`ResultExt`](crate::ResultExt), but
+/// apply to every item of the stream.
+///
+/// Once the body of a streamed response has started, its status code and headers have
+/// already been sent, so a failing item can no longer become an error response. Passing
+/// the error on to the response body aborts the connection, and the client sees a
+/// truncated body. [`end_with_error`](TryStreamExt::end_with_error) instead turns the
+/// first error into a final record, so the client can tell that the stream failed.
+///
+/// Like [`SseStream`](crate::SseStream), the returned streams keep the request context
+/// of the [`ErrorInterceptorLayer`](crate::ErrorInterceptorLayer) and the error format
+/// that were current when they were created. Errors converted while the body is polled,
+/// after the handler has returned, are therefore still enriched with the originating
+/// request and rendered in the format of the route.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::body::Body;
+/// use axum_anyhow::{ApiErrorResponse, TryStreamExt};
+/// use futures_util::{stream, StreamExt};
+/// use serde_json::json;
+/// use std::convert::Infallible;
+///
+/// async fn export() -> Body {
+///     let rows = stream::iter(vec![
+///         Ok(json!({"id": 1})),
+///         Ok(json!({"id": 2})),
+///         Err(anyhow::anyhow!("connection lost")),
+///     ]);
+///
+///     let lines = rows
+///         .context_internal("Export Failed")
+///         .end_with_error(|err| json!({ "error": ApiErrorResponse::from(err) }))
+///         .map(|record| Ok::<_, Infallible>(format!("{record}\n")));
+///
+///     Body::from_stream(lines)
+/// }
+/// ```
+///
+/// The body of this response has three lines, the last one being
+/// `{"error":{"status":500,"title":"Export Failed"}}`.
+///
+/// # Sealed Trait
+///
+/// This trait is sealed and cannot be implemented for types outside this crate.
+pub trait TryStreamExt<T, E>: Stream<Item = Result<T, E>> + sealed::SealedTryStream {
+    /// Converts each item error to an `ApiError` with a custom status code.
+    fn context_status(
+        self,
+        status: StatusCode,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 400 Bad Request error.
+    fn context_bad_request(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 401 Unauthorized error.
+    fn context_unauthorized(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 403 Forbidden error.
+    fn context_forbidden(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 404 Not Found error.
+    fn context_not_found(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 405 Method Not Allowed error.
+    fn context_method_not_allowed(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 409 Conflict error.
+    fn context_conflict(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 422 Unprocessable Entity error.
+    fn context_unprocessable_entity(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 429 Too Many Requests error.
+    fn context_too_many_requests(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 500 Internal Server Error.
+    fn context_internal(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 502 Bad Gateway error.
+    fn context_bad_gateway(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 503 Service Unavailable error.
+    fn context_service_unavailable(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Converts each item error to a 504 Gateway Timeout error.
+    fn context_gateway_timeout(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError;
+
+    /// Yields the `Ok` items, and ends the stream after turning the first error into a
+    /// final item with `f`.
+    ///
+    /// Items after the first error are never polled.
+    fn end_with_error<F>(self, f: F) -> impl Stream<Item = T>
+    where
+        E: Into<ApiError>,
+        F: FnOnce(ApiError) -> T;
+}
+
+impl<S, T, E> TryStreamExt<T, E> for S
+where
+    S: Stream<Item = Result<T, E>>,
+{
+    fn context_status(
+        self,
+        status: StatusCode,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        let context = context.into();
+        RequestScoped::new(
+            self.map(move |item| item.map_err(|err| err.context_status(status, context.clone()))),
+        )
+    }
+
+    fn context_bad_request(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::BAD_REQUEST, context)
+    }
+
+    fn context_unauthorized(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::UNAUTHORIZED, context)
+    }
+
+    fn context_forbidden(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::FORBIDDEN, context)
+    }
+
+    fn context_not_found(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::NOT_FOUND, context)
+    }
+
+    fn context_method_not_allowed(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::METHOD_NOT_ALLOWED, context)
+    }
+
+    fn context_conflict(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::CONFLICT, context)
+    }
+
+    fn context_unprocessable_entity(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::UNPROCESSABLE_ENTITY, context)
+    }
+
+    fn context_too_many_requests(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::TOO_MANY_REQUESTS, context)
+    }
+
+    fn context_internal(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::INTERNAL_SERVER_ERROR, context)
+    }
+
+    fn context_bad_gateway(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::BAD_GATEWAY, context)
+    }
+
+    fn context_service_unavailable(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::SERVICE_UNAVAILABLE, context)
+    }
+
+    fn context_gateway_timeout(
+        self,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Stream<Item = ApiResult<T>>
+    where
+        E: IntoApiError,
+    {
+        self.context_status(StatusCode::GATEWAY_TIMEOUT, context)
+    }
+
+    fn end_with_error<F>(self, f: F) -> impl Stream<Item = T>
+    where
+        E: Into<ApiError>,
+        F: FnOnce(ApiError) -> T,
+    {
+        RequestScoped::new(stream::unfold(
+            Some((Box::pin(self), f)),
+            |state| async move {
+                let (mut items, f) = state?;
+                match items.next().await? {
+                    Ok(item) => Some((item, Some((items, f)))),
+                    Err(err) => Some((f(err.into()), None)),
+                }
+            },
+        ))
+    }
+}
+
+/// A stream that installs the request context and error format of the handler that
+/// created it while it is polled.
+struct RequestScoped<S> {
+    stream: Pin<Box<S>>,
+    enrichment: Option<EnrichmentContext>,
+    format: ErrorFormat,
+}
+
+impl<S> RequestScoped<S> {
+    fn new(stream: S) -> Self {
+        Self {
+            stream: Box::pin(stream),
+            enrichment: EnrichmentContext::current(),
+            format: error_format(),
+        }
+    }
+}
+
+impl<S: Stream> Stream for RequestScoped<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let stream = &mut this.stream;
+        with_error_format(this.format, || {
+            EnrichmentContext::scope(&mut this.enrichment, || stream.as_mut().poll_next(cx))
+        })
+    }
+}
+
+mod sealed {
+    pub trait SealedTryStream {}
+
+    impl<S, T, E> SealedTryStream for S where S: futures_util::Stream<Item = Result<T, E>> {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ApiErrorResponse;
+    use anyhow::anyhow;
+    use axum::{body::Body, extract::Request, routing::get, Router};
+    use http_body_util::BodyExt;
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+
+    fn rows() -> impl Stream<Item = anyhow::Result<u32>> + Send + 'static {
+        stream::iter(vec![Ok(1), Err(anyhow!("connection lost")), Ok(3)])
+    }
+
+    #[tokio::test]
+    async fn test_context_maps_each_error() {
+        let items: Vec<_> = rows()
+            .context_service_unavailable(("Export Failed", "The database went away"))
+            .collect()
+            .await;
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(*items[0].as_ref().unwrap(), 1);
+        let err = items[1].as_ref().unwrap_err();
+        assert_eq!(err.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(err.detail(), Some("The database went away"));
+        assert_eq!(err.error().unwrap().to_string(), "connection lost");
+    }
+
+    #[tokio::test]
+    async fn test_end_with_error_stops_after_first_error() {
+        let items: Vec<_> = rows()
+            .context_internal("Export Failed")
+            .end_with_error(|err| err.status().as_u16() as u32)
+            .collect()
+            .await;
+
+        assert_eq!(items, [1, 500]);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_response_ends_with_error_record() {
+        async fn handler() -> Body {
+            let lines = rows()
+                .map(|row| row.map(|id| serde_json::json!({ "id": id })))
+                .context_internal("Export Failed")
+                .end_with_error(|err| serde_json::json!({ "error": ApiErrorResponse::from(err) }))
+                .map(|record| Ok::<_, Infallible>(format!("{record}\n")));
+            Body::from_stream(lines)
+        }
+
+        let app = Router::new().route("/", get(handler));
+        let response = app
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+
+        assert_eq!(
+            std::str::from_utf8(&body).unwrap(),
+            "{\"id\":1}\n{\"error\":{\"status\":500,\"title\":\"Export Failed\"}}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_record_keeps_request_context_and_format() {
+        use crate::{error_format, ErrorFormat, ErrorFormatLayer, ErrorInterceptorLayer};
+
+        async fn handler() -> Body {
+            let lines = rows()
+                .map(|row| row.map(|id| id.to_string()))
+                .context_internal("Export Failed")
+                .end_with_error(|err| {
+                    serde_json::json!({
+                        "format": format!("{:?}", error_format()),
+                        "meta": err.meta(),
+                    })
+                    .to_string()
+                })
+                .map(|line| Ok::<_, Infallible>(format!("{line}\n")));
+            Body::from_stream(lines)
+        }
+
+        let app = Router::new()
+            .route("/export", get(handler))
+            .layer(ErrorFormatLayer::new(ErrorFormat::Twirp))
+            .layer(ErrorInterceptorLayer::new(|builder, request| {
+                builder.meta(serde_json::json!({ "path": request.uri().path() }))
+            }));
+        let response = app
+            .oneshot(Request::get("/export").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let last = std::str::from_utf8(&body).unwrap().lines().last().unwrap();
+
+        assert_eq!(
+            last,
+            "{\"format\":\"Twirp\",\"meta\":{\"path\":\"/export\"}}"
+        );
+    }
+}
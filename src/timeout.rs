@@ -0,0 +1,218 @@
+//! Timeouts for futures that return results.
+//!
+//! Wrapping an upstream call in `tokio::time::timeout` yields a nested
+//! `Result<Result<T, E>, Elapsed>`. [`FutureExt`] flattens it into an `ApiResult<T>`,
+//! turning the timeout into an error with the duration recorded in the metadata.
+
+use crate::{ApiError, ApiErrorContext, ApiResult};
+use axum::http::StatusCode;
+use serde_json::json;
+use std::{future::Future, time::Duration};
+
+/// Extension trait for futures that return a `Result`, to give them a deadline.
+///
+/// If the future completes in time, its error is converted the same way as with the `?`
+/// operator: an `ApiError` is returned unchanged and any other error becomes a 500
+/// Internal Server Error. If the deadline passes first, the future is dropped and the
+/// result is an error with the given status code and context, and the time spent waiting
+/// in milliseconds under `elapsed_ms` in the metadata.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum_anyhow::{ApiResult, FutureExt};
+/// use std::time::Duration;
+///
+/// async fn fetch_quote() -> anyhow::Result<u32> {
+///     tokio::time::sleep(Duration::from_secs(10)).await;
+///     Ok(42)
+/// }
+///
+/// async fn get_quote() -> ApiResult<u32> {
+///     fetch_quote()
+///         .timeout_gateway(Duration::from_millis(10), ("Quote Service Timeout", "No quote in time"))
+///         .await
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let err = get_quote().await.unwrap_err();
+/// assert_eq!(err.status(), StatusCode::GATEWAY_TIMEOUT);
+/// assert!(err.meta().unwrap()["elapsed_ms"].as_u64().unwrap() >= 10);
+/// # });
+/// ```
+///
+/// # Sealed Trait
+///
+/// This trait is sealed and cannot be implemented for types outside this crate.
+pub trait FutureExt<T, E>: sealed::SealedFuture {
+    /// Fails with a custom status code if the future does not complete within `duration`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::{ApiResult, FutureExt};
+    /// use std::time::Duration;
+    ///
+    /// async fn read_upload() -> ApiResult<Vec<u8>> {
+    ///     tokio::time::sleep(Duration::from_secs(10)).await;
+    ///     Ok(Vec::new())
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let err = read_upload()
+    ///     .timeout_status(Duration::from_millis(10), StatusCode::REQUEST_TIMEOUT, "Upload Too Slow")
+    ///     .await
+    ///     .unwrap_err();
+    /// assert_eq!(err.status(), StatusCode::REQUEST_TIMEOUT);
+    /// # });
+    /// ```
+    fn timeout_status(
+        self,
+        duration: Duration,
+        status: StatusCode,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Future<Output = ApiResult<T>>;
+
+    /// Fails with a 408 Request Timeout error if the future does not complete in time.
+    fn timeout_request(
+        self,
+        duration: Duration,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Future<Output = ApiResult<T>>;
+
+    /// Fails with a 504 Gateway Timeout error if the future does not complete in time.
+    fn timeout_gateway(
+        self,
+        duration: Duration,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Future<Output = ApiResult<T>>;
+}
+
+impl<F, T, E> FutureExt<T, E> for F
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<ApiError>,
+{
+    fn timeout_status(
+        self,
+        duration: Duration,
+        status: StatusCode,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Future<Output = ApiResult<T>> {
+        let context = context.into();
+        async move {
+            let start = tokio::time::Instant::now();
+            match tokio::time::timeout(duration, self).await {
+                Ok(result) => result.map_err(Into::into),
+                Err(elapsed) => {
+                    let elapsed_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+                    let builder = ApiError::builder()
+                        .status(status)
+                        .meta(json!({ "elapsed_ms": elapsed_ms }))
+                        .error(elapsed);
+                    Err(context.apply(builder).build())
+                }
+            }
+        }
+    }
+
+    fn timeout_request(
+        self,
+        duration: Duration,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Future<Output = ApiResult<T>> {
+        self.timeout_status(duration, StatusCode::REQUEST_TIMEOUT, context)
+    }
+
+    fn timeout_gateway(
+        self,
+        duration: Duration,
+        context: impl Into<ApiErrorContext>,
+    ) -> impl Future<Output = ApiResult<T>> {
+        self.timeout_status(duration, StatusCode::GATEWAY_TIMEOUT, context)
+    }
+}
+
+mod sealed {
+    pub trait SealedFuture {}
+
+    impl<F, T, E> SealedFuture for F where F: std::future::Future<Output = Result<T, E>> {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IntoApiError;
+    use anyhow::anyhow;
+    use axum::{body::Body, extract::Request, routing::get, Router};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeout_records_elapsed_and_context() {
+        let err = std::future::pending::<anyhow::Result<()>>()
+            .timeout_gateway(
+                Duration::from_millis(1500),
+                ("Inventory Timeout", "The inventory service did not respond"),
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(err.title(), "Inventory Timeout");
+        // The paused clock advances exactly to the deadline
+        assert_eq!(err.meta(), Some(&json!({"elapsed_ms": 1500})));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_measures_elapsed_time() {
+        let err = std::future::pending::<anyhow::Result<()>>()
+            .timeout_request(Duration::from_millis(20), "Timeout")
+            .await
+            .unwrap_err();
+
+        let elapsed_ms = err.meta().unwrap()["elapsed_ms"].as_u64().unwrap();
+        assert!(elapsed_ms >= 20, "{elapsed_ms}");
+        assert_eq!(err.error().unwrap().to_string(), "deadline has elapsed");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_passes_through_inner_results() {
+        let value = async { anyhow::Ok(7) }
+            .timeout_gateway(Duration::from_secs(1), "Timeout")
+            .await
+            .unwrap();
+        assert_eq!(value, 7);
+
+        let err = async { Err::<(), _>(anyhow!("boom")) }
+            .timeout_gateway(Duration::from_secs(1), "Timeout")
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let err = async { Err::<(), _>(anyhow!("gone").context_not_found("Not Found")) }
+            .timeout_gateway(Duration::from_secs(1), "Timeout")
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeout_in_handler() {
+        async fn handler() -> ApiResult<&'static str> {
+            std::future::pending::<ApiResult<&'static str>>()
+                .timeout_request(Duration::from_secs(30), "Request Timeout")
+                .await
+        }
+
+        let app = Router::new().route("/", get(handler));
+        let response = app
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+}
@@ -0,0 +1,244 @@
+//! [Twirp](https://twitchtv.github.io/twirp/docs/spec_v7.html#error-codes) error format.
+//!
+//! Errors are rendered as a Twirp error, with the metadata of the error flattened into
+//! the string map `meta`:
+//!
+//! ```json
+//! {
+//!   "code": "not_found",
+//!   "msg": "User Not Found: No user with ID 42",
+//!   "meta": { "user_id": "42" }
+//! }
+//! ```
+
+use crate::{google::metadata, ApiError, CanonicalCode};
+use anyhow::anyhow;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Map, Value};
+
+impl ApiError {
+    /// Creates an `ApiError` from the status and body of a Twirp error response.
+    ///
+    /// The Twirp code is mapped to the HTTP status code from the Twirp specification,
+    /// and a message of the form `"title: detail"` is split back into title and detail.
+    /// The `meta` map becomes the error metadata. If the body is not a Twirp error, the
+    /// canonical reason of the status code is used as the title.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::ApiError;
+    ///
+    /// let body = br#"{"code": "permission_denied", "msg": "Forbidden", "meta": {"role": "viewer"}}"#;
+    /// let error = ApiError::from_twirp(StatusCode::FORBIDDEN, body);
+    ///
+    /// assert_eq!(error.status(), StatusCode::FORBIDDEN);
+    /// assert_eq!(error.title(), "Forbidden");
+    /// assert_eq!(error.meta().unwrap()["role"], "viewer");
+    /// ```
+    pub fn from_twirp(status: StatusCode, body: &[u8]) -> ApiError {
+        let body = match serde_json::from_slice::<Value>(body) {
+            Ok(body) if body["code"].is_string() => body,
+            Ok(_) => return unrecognized(status, anyhow!("missing Twirp error code")),
+            Err(err) => return unrecognized(status, anyhow!(err)),
+        };
+        let code = body["code"].as_str().unwrap_or_default();
+        let message = body["msg"].as_str().unwrap_or_default();
+
+        let mut builder = ApiError::builder().status(to_http(code).unwrap_or(status));
+        builder = match message.split_once(": ") {
+            Some((title, detail)) => builder.title(title).detail(detail),
+            None if message.is_empty() => builder.title(code),
+            None => builder.title(message),
+        };
+        if let Some(meta) = body["meta"].as_object().filter(|meta| !meta.is_empty()) {
+            builder = builder.meta(Value::Object(meta.clone()));
+        }
+
+        builder
+            .error(anyhow!("Twirp error {code}: {message}"))
+            .build()
+    }
+}
+
+/// Renders an `ApiError` as a Twirp error response.
+///
+/// The HTTP status code is the one that the Twirp specification assigns to the code in
+/// the body, so a 504 is sent as `deadline_exceeded` with status 408, and a 2xx error as
+/// `unknown` with status 500.
+pub(crate) fn into_response(error: ApiError) -> Response {
+    let status = to_http(code(error.canonical_code())).expect("every Twirp code has a status");
+    (status, Json(to_json(&error))).into_response()
+}
+
+/// Builds the Twirp error document.
+pub(crate) fn to_json(error: &ApiError) -> Value {
+    let mut body = Map::new();
+    body.insert("code".to_string(), json!(code(error.canonical_code())));
+    body.insert("msg".to_string(), json!(error.message()));
+    let metadata = error.meta().map(metadata).unwrap_or_default();
+    if !metadata.is_empty() {
+        body.insert("meta".to_string(), json!(metadata));
+    }
+    Value::Object(body)
+}
+
+/// Maps a canonical code to the Twirp error code.
+fn code(code: CanonicalCode) -> &'static str {
+    match code {
+        CanonicalCode::Ok | CanonicalCode::Unknown => "unknown",
+        CanonicalCode::Cancelled => "canceled",
+        CanonicalCode::InvalidArgument => "invalid_argument",
+        CanonicalCode::DeadlineExceeded => "deadline_exceeded",
+        CanonicalCode::NotFound => "not_found",
+        CanonicalCode::AlreadyExists => "already_exists",
+        CanonicalCode::PermissionDenied => "permission_denied",
+        CanonicalCode::ResourceExhausted => "resource_exhausted",
+        CanonicalCode::FailedPrecondition => "failed_precondition",
+        CanonicalCode::Aborted => "aborted",
+        CanonicalCode::OutOfRange => "out_of_range",
+        CanonicalCode::Unimplemented => "unimplemented",
+        CanonicalCode::Internal => "internal",
+        CanonicalCode::Unavailable => "unavailable",
+        CanonicalCode::DataLoss => "dataloss",
+        CanonicalCode::Unauthenticated => "unauthenticated",
+    }
+}
+
+/// Maps a Twirp error code to the HTTP status code from the Twirp specification.
+fn to_http(code: &str) -> Option<StatusCode> {
+    let status = match code {
+        "canceled" | "deadline_exceeded" => StatusCode::REQUEST_TIMEOUT,
+        "invalid_argument" | "malformed" | "out_of_range" => StatusCode::BAD_REQUEST,
+        "not_found" | "bad_route" => StatusCode::NOT_FOUND,
+        "already_exists" | "aborted" => StatusCode::CONFLICT,
+        "permission_denied" => StatusCode::FORBIDDEN,
+        "unauthenticated" => StatusCode::UNAUTHORIZED,
+        "resource_exhausted" => StatusCode::TOO_MANY_REQUESTS,
+        "failed_precondition" => StatusCode::PRECONDITION_FAILED,
+        "unimplemented" => StatusCode::NOT_IMPLEMENTED,
+        "unknown" | "internal" | "dataloss" => StatusCode::INTERNAL_SERVER_ERROR,
+        "unavailable" => StatusCode::SERVICE_UNAVAILABLE,
+        _ => return None,
+    };
+    Some(status)
+}
+
+fn unrecognized(status: StatusCode, err: anyhow::Error) -> ApiError {
+    ApiError::builder()
+        .status(status)
+        .title(status.canonical_reason().unwrap_or("Unknown"))
+        .error(err.context(format!(
+            "Twirp peer returned status {status} with an unrecognized error body"
+        )))
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorFormat;
+    use http_body_util::BodyExt;
+
+    async fn body(error: ApiError) -> Value {
+        let response = error.into_response_as(ErrorFormat::Twirp);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_twirp_error_with_meta() {
+        let error = ApiError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("User Not Found")
+            .detail("No user with ID 42")
+            .meta(json!({"user_id": 42, "tenant": "acme"}))
+            .build();
+
+        assert_eq!(
+            body(error).await,
+            json!({
+                "code": "not_found",
+                "msg": "User Not Found: No user with ID 42",
+                "meta": {"user_id": "42", "tenant": "acme"},
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_twirp_codes() {
+        let error = ApiError::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .title("Rate Limited")
+            .build();
+        assert_eq!(
+            body(error).await,
+            json!({"code": "resource_exhausted", "msg": "Rate Limited"})
+        );
+
+        let code = |status| code(CanonicalCode::from_http(status));
+        assert_eq!(code(StatusCode::GATEWAY_TIMEOUT), "deadline_exceeded");
+        assert_eq!(code(StatusCode::PRECONDITION_FAILED), "failed_precondition");
+        assert_eq!(super::code(CanonicalCode::Aborted), "aborted");
+    }
+
+    #[tokio::test]
+    async fn test_twirp_http_status_follows_code() {
+        let response = ApiError::builder()
+            .status(StatusCode::OK)
+            .title("Partial Failure")
+            .build()
+            .into_response_as(ErrorFormat::Twirp);
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["code"], "unknown");
+
+        let response = ApiError::builder()
+            .status(StatusCode::GATEWAY_TIMEOUT)
+            .build()
+            .into_response_as(ErrorFormat::Twirp);
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+
+        let response = ApiError::builder()
+            .status(StatusCode::BAD_GATEWAY)
+            .build()
+            .into_response_as(ErrorFormat::Twirp);
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_from_twirp_uses_twirp_status_table() {
+        let body = br#"{"code": "deadline_exceeded", "msg": "Upstream Slow: No reply"}"#;
+        let error = ApiError::from_twirp(StatusCode::INTERNAL_SERVER_ERROR, body);
+
+        assert_eq!(error.status(), StatusCode::REQUEST_TIMEOUT);
+        assert_eq!(error.title(), "Upstream Slow");
+        assert_eq!(error.detail(), Some("No reply"));
+        assert_eq!(
+            error.error().unwrap().to_string(),
+            "Twirp error deadline_exceeded: Upstream Slow: No reply"
+        );
+
+        let error = ApiError::from_twirp(
+            StatusCode::NOT_FOUND,
+            br#"{"code": "bad_route", "msg": ""}"#,
+        );
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+        assert_eq!(error.title(), "bad_route");
+    }
+
+    #[test]
+    fn test_from_twirp_unrecognized_body() {
+        let error =
+            ApiError::from_twirp(StatusCode::SERVICE_UNAVAILABLE, b"upstream connect error");
+
+        assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(error.title(), "Service Unavailable");
+    }
+}
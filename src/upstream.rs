@@ -0,0 +1,320 @@
+//! Mapping of HTTP client errors from upstream calls.
+//!
+//! When a handler calls another service, failures of that call are not bugs in the
+//! handler. This module classifies `reqwest` and `hyper` client errors so that timeouts
+//! become `504 Gateway Timeout` and every other upstream failure becomes
+//! `502 Bad Gateway`, labelled with the name of the dependency that failed.
+
+use crate::{ApiError, ApiResult};
+use axum::http::StatusCode;
+use serde_json::{json, Map, Value};
+
+/// Extension trait for results of calls to upstream services.
+///
+/// | Upstream failure                   | Status code             |
+/// | ---------------------------------- | ----------------------- |
+/// | Timeout                            | 504 Gateway Timeout     |
+/// | Connect or DNS error               | 502 Bad Gateway         |
+/// | Response body could not be decoded | 502 Bad Gateway         |
+/// | Upstream returned an error status  | 502 Bad Gateway         |
+/// | Request could not be built         | 500 Internal Error      |
+///
+/// The name of the upstream is recorded in the error metadata under `upstream`, along
+/// with the upstream `url` for decode errors and the `upstream_status` for error
+/// statuses.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "reqwest")]
+/// # {
+/// use axum_anyhow::{ApiResult, UpstreamResultExt};
+///
+/// async fn charge(client: reqwest::Client) -> ApiResult<String> {
+///     let response = client
+///         .post("http://billing.internal/charges")
+///         .send()
+///         .await
+///         .and_then(|response| response.error_for_status())
+///         .upstream_context("billing")?;
+///
+///     response.text().await.upstream_context("billing")
+/// }
+/// # }
+/// ```
+///
+/// # Sealed Trait
+///
+/// This trait is sealed and cannot be implemented for types outside this crate.
+pub trait UpstreamResultExt<T>: sealed::SealedUpstreamResult {
+    /// Converts a client error to an `ApiError`, labelled with the name of the upstream.
+    fn upstream_context(self, upstream: impl Into<String>) -> ApiResult<T>;
+}
+
+/// How an upstream failure should be reported.
+// The `hyper` client never reports `Status` or `Builder` failures.
+#[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+enum UpstreamFailure {
+    /// The upstream did not respond in time
+    Timeout,
+    /// The upstream could not be reached
+    Connect,
+    /// The upstream response could not be decoded
+    Decode { url: Option<String> },
+    /// The upstream responded with an error status
+    Status(StatusCode),
+    /// The request could not be sent for another reason
+    Request,
+    /// The request could not be built
+    Builder,
+}
+
+impl<T, E> UpstreamResultExt<T> for Result<T, E>
+where
+    E: sealed::UpstreamError,
+{
+    fn upstream_context(self, upstream: impl Into<String>) -> ApiResult<T> {
+        self.map_err(|err| err.into_upstream_error(upstream.into()))
+    }
+}
+
+fn upstream_error(
+    err: impl Into<anyhow::Error>,
+    failure: UpstreamFailure,
+    upstream: String,
+) -> ApiError {
+    let mut meta = Map::new();
+    let (status, title, detail) = match failure {
+        UpstreamFailure::Timeout => (
+            StatusCode::GATEWAY_TIMEOUT,
+            "Gateway Timeout",
+            format!("Upstream '{upstream}' did not respond in time"),
+        ),
+        UpstreamFailure::Connect => (
+            StatusCode::BAD_GATEWAY,
+            "Bad Gateway",
+            format!("Upstream '{upstream}' could not be reached"),
+        ),
+        UpstreamFailure::Decode { url } => {
+            if let Some(url) = url {
+                meta.insert("url".to_string(), Value::String(url));
+            }
+            (
+                StatusCode::BAD_GATEWAY,
+                "Bad Gateway",
+                format!("Upstream '{upstream}' returned an invalid response"),
+            )
+        }
+        UpstreamFailure::Status(upstream_status) => {
+            meta.insert(
+                "upstream_status".to_string(),
+                json!(upstream_status.as_u16()),
+            );
+            (
+                StatusCode::BAD_GATEWAY,
+                "Bad Gateway",
+                format!("Upstream '{upstream}' returned status {upstream_status}"),
+            )
+        }
+        UpstreamFailure::Request => (
+            StatusCode::BAD_GATEWAY,
+            "Bad Gateway",
+            format!("Request to upstream '{upstream}' failed"),
+        ),
+        UpstreamFailure::Builder => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal Error",
+            format!("Request to upstream '{upstream}' could not be built"),
+        ),
+    };
+    meta.insert("upstream".to_string(), Value::String(upstream));
+
+    ApiError::builder()
+        .status(status)
+        .title(title)
+        .detail(detail)
+        .meta(Value::Object(meta))
+        .error(err)
+        .build()
+}
+
+#[cfg(feature = "reqwest")]
+impl sealed::UpstreamError for reqwest::Error {
+    fn into_upstream_error(self, upstream: String) -> ApiError {
+        let failure = reqwest_failure(&self);
+        upstream_error(self, failure, upstream)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+fn reqwest_failure(err: &reqwest::Error) -> UpstreamFailure {
+    if err.is_timeout() {
+        UpstreamFailure::Timeout
+    } else if err.is_connect() || err.is_dns() {
+        UpstreamFailure::Connect
+    } else if err.is_decode() {
+        UpstreamFailure::Decode {
+            url: err.url().map(|url| url.to_string()),
+        }
+    } else if let Some(status) = err.status() {
+        UpstreamFailure::Status(status)
+    } else if err.is_builder() {
+        UpstreamFailure::Builder
+    } else {
+        UpstreamFailure::Request
+    }
+}
+
+#[cfg(feature = "hyper-client")]
+impl sealed::UpstreamError for hyper_util::client::legacy::Error {
+    fn into_upstream_error(self, upstream: String) -> ApiError {
+        let failure = hyper_failure(&self);
+        upstream_error(self, failure, upstream)
+    }
+}
+
+#[cfg(feature = "hyper-client")]
+fn hyper_failure(client_err: &hyper_util::client::legacy::Error) -> UpstreamFailure {
+    let mut source = std::error::Error::source(client_err);
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<hyper::Error>() {
+            if err.is_timeout() {
+                return UpstreamFailure::Timeout;
+            }
+            if err.is_parse() || err.is_parse_status() || err.is_incomplete_message() {
+                return UpstreamFailure::Decode { url: None };
+            }
+        }
+        if let Some(err) = err.downcast_ref::<std::io::Error>() {
+            if err.kind() == std::io::ErrorKind::TimedOut {
+                return UpstreamFailure::Timeout;
+            }
+        }
+        source = err.source();
+    }
+
+    if client_err.is_connect() {
+        UpstreamFailure::Connect
+    } else {
+        UpstreamFailure::Request
+    }
+}
+
+mod sealed {
+    use crate::ApiError;
+
+    /// A client error that can be classified and converted into an upstream `ApiError`.
+    pub trait UpstreamError: Into<anyhow::Error> {
+        fn into_upstream_error(self, upstream: String) -> ApiError;
+    }
+
+    pub trait SealedUpstreamResult {}
+
+    impl<T, E> SealedUpstreamResult for Result<T, E> where E: UpstreamError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "reqwest")]
+    async fn serve(app: axum::Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_reqwest_connect_error_is_bad_gateway() {
+        let result = reqwest::get("http://127.0.0.1:1/").await;
+        let err = result.upstream_context("billing").unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(err.title(), "Bad Gateway");
+        assert_eq!(
+            err.detail(),
+            Some("Upstream 'billing' could not be reached")
+        );
+        assert_eq!(err.meta().unwrap()["upstream"], "billing");
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_reqwest_timeout_is_gateway_timeout() {
+        use axum::routing::get;
+        use std::time::Duration;
+
+        let app = axum::Router::new().route(
+            "/",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "late"
+            }),
+        );
+        let base = serve(app).await;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(50))
+            .build()
+            .unwrap();
+        let result = client.get(&base).send().await;
+        let err = result.upstream_context("billing").unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(err.meta().unwrap()["upstream"], "billing");
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_reqwest_decode_error_includes_url() {
+        use axum::routing::get;
+
+        let app = axum::Router::new().route("/invoice", get(|| async { "not json" }));
+        let base = serve(app).await;
+
+        let response = reqwest::get(format!("{base}/invoice")).await.unwrap();
+        let result = response.json::<Value>().await;
+        let err = result.upstream_context("billing").unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::BAD_GATEWAY);
+        let meta = err.meta().unwrap();
+        assert_eq!(meta["upstream"], "billing");
+        assert_eq!(meta["url"], format!("{base}/invoice"));
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_reqwest_upstream_5xx_is_bad_gateway() {
+        use axum::routing::get;
+
+        let app = axum::Router::new().route(
+            "/",
+            get(|| async { (StatusCode::SERVICE_UNAVAILABLE, "down") }),
+        );
+        let base = serve(app).await;
+
+        let result = reqwest::get(&base)
+            .await
+            .and_then(|response| response.error_for_status());
+        let err = result.upstream_context("billing").unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(err.meta().unwrap()["upstream_status"], 503);
+    }
+
+    #[cfg(feature = "hyper-client")]
+    #[tokio::test]
+    async fn test_hyper_connect_error_is_bad_gateway() {
+        use axum::body::Body;
+        use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+
+        let client = Client::builder(TokioExecutor::new()).build_http::<Body>();
+        let result = client.get("http://127.0.0.1:1/".parse().unwrap()).await;
+        let err = result.upstream_context("inventory").unwrap_err();
+
+        assert_eq!(err.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(err.meta().unwrap()["upstream"], "inventory");
+    }
+}
@@ -0,0 +1,405 @@
+//! Integration with the `validator` and `garde` validation crates.
+//!
+//! Validation failures are converted into [`FieldErrors`], which become a
+//! `422 Unprocessable Entity` `ApiError` with one [`FieldError`] per invalid field. The
+//! [`Valid`] and [`Garde`] extractors run validation right after deserializing the
+//! request body.
+
+use crate::{ApiError, FieldError, FieldErrors};
+use axum::{
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    Json,
+};
+
+/// An extractor that validates a JSON request body with the `validator` crate.
+///
+/// If the body cannot be deserialized, the request is rejected with the status of the
+/// [`Json`] rejection. If validation fails, it is rejected with a
+/// `422 Unprocessable Entity` error listing every invalid field.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::Json;
+/// use axum_anyhow::{ApiResult, Valid};
+/// use serde::Deserialize;
+/// use validator::Validate;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct CreateUser {
+///     #[validate(length(min = 1))]
+///     name: String,
+///     #[validate(range(min = 18))]
+///     age: u32,
+/// }
+///
+/// async fn create_user(Valid(Json(user)): Valid<Json<CreateUser>>) -> ApiResult<String> {
+///     Ok(user.name)
+/// }
+/// ```
+#[cfg(feature = "validator")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Valid<E>(pub E);
+
+/// An extractor that validates a JSON request body with the `garde` crate.
+///
+/// Rejections work the same way as for [`Valid`]. The validation context must
+/// implement `Default`.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::Json;
+/// use axum_anyhow::{ApiResult, Garde};
+/// use garde::Validate;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct CreateUser {
+///     #[garde(length(min = 1))]
+///     name: String,
+///     #[garde(range(min = 18))]
+///     age: u32,
+/// }
+///
+/// async fn create_user(Garde(Json(user)): Garde<Json<CreateUser>>) -> ApiResult<String> {
+///     Ok(user.name)
+/// }
+/// ```
+#[cfg(feature = "garde")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Garde<E>(pub E);
+
+#[cfg(feature = "validator")]
+impl<S, T> FromRequest<S> for Valid<Json<T>>
+where
+    Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    T: validator::Validate,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(json_rejection)?;
+        value.validate().map_err(FieldErrors::from)?;
+        Ok(Valid(Json(value)))
+    }
+}
+
+#[cfg(feature = "garde")]
+impl<S, T> FromRequest<S> for Garde<Json<T>>
+where
+    Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    T: garde::Validate,
+    T::Context: Default,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(json_rejection)?;
+        value.validate().map_err(FieldErrors::from)?;
+        Ok(Garde(Json(value)))
+    }
+}
+
+/// Converts a JSON rejection into an `ApiError` with the same status code.
+fn json_rejection(rejection: JsonRejection) -> ApiError {
+    let status = rejection.status();
+    ApiError::builder()
+        .status(status)
+        .title(status.canonical_reason().unwrap_or("Invalid Request"))
+        .detail(rejection.body_text())
+        .error(rejection)
+        .build()
+}
+
+/// Converts `validator` errors into field errors.
+///
+/// Nested structs and lists become JSON Pointers such as `/address/city` or
+/// `/items/0/name`, and struct-level errors point at the enclosing object. The
+/// rule name becomes the code, and the rule parameters, except for the rejected
+/// `value`, become the params.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::{ApiError, FieldErrors};
+/// use validator::Validate;
+///
+/// #[derive(Validate)]
+/// struct Signup {
+///     #[validate(email)]
+///     email: String,
+/// }
+///
+/// let signup = Signup { email: "invalid".to_string() };
+/// let error = ApiError::from(FieldErrors::from(signup.validate().unwrap_err()));
+///
+/// assert_eq!(error.status(), 422);
+/// assert_eq!(error.field_errors()[0].pointer, "/email");
+/// assert_eq!(error.field_errors()[0].code.as_deref(), Some("email"));
+/// ```
+#[cfg(feature = "validator")]
+impl From<validator::ValidationErrors> for FieldErrors {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        let mut field_errors = FieldErrors::new();
+        collect_validator_errors(&mut field_errors, "", &errors);
+        field_errors
+    }
+}
+
+#[cfg(feature = "validator")]
+fn collect_validator_errors(
+    field_errors: &mut FieldErrors,
+    pointer: &str,
+    errors: &validator::ValidationErrors,
+) {
+    use validator::ValidationErrorsKind;
+
+    let mut entries = errors.errors().iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(field, _)| *field);
+
+    for (field, kind) in entries {
+        let pointer = match field.as_ref() {
+            "__all__" => pointer.to_string(),
+            field => format!("{pointer}/{}", escape(field)),
+        };
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                for error in errors {
+                    let message = match &error.message {
+                        Some(message) => message.to_string(),
+                        None => format!("Invalid {}", error.code),
+                    };
+                    let mut field_error =
+                        FieldError::new(&pointer, message).code(error.code.clone());
+                    let mut params = error.params.iter().collect::<Vec<_>>();
+                    params.sort_by_key(|(name, _)| *name);
+                    for (name, value) in params {
+                        if name != "value" {
+                            field_error = field_error.param(name.clone(), value.clone());
+                        }
+                    }
+                    field_errors.push(field_error);
+                }
+            }
+            ValidationErrorsKind::Struct(errors) => {
+                collect_validator_errors(field_errors, &pointer, errors);
+            }
+            ValidationErrorsKind::List(errors) => {
+                for (index, errors) in errors {
+                    collect_validator_errors(field_errors, &format!("{pointer}/{index}"), errors);
+                }
+            }
+        }
+    }
+}
+
+/// Converts a `garde` report into field errors.
+///
+/// Paths such as `address.city` or `items[0].name` become JSON Pointers such as
+/// `/address/city` or `/items/0/name`.
+#[cfg(feature = "garde")]
+impl From<garde::Report> for FieldErrors {
+    fn from(report: garde::Report) -> Self {
+        report
+            .iter()
+            .map(|(path, error)| FieldError::new(garde_pointer(path), error.message()))
+            .collect()
+    }
+}
+
+/// Converts a `garde` path such as `items[0].name` into a JSON Pointer.
+///
+/// The path is read component by component rather than parsed from its `Display` form,
+/// which joins keys with `.` and cannot be split back when a map key contains one.
+#[cfg(feature = "garde")]
+fn garde_pointer(path: &garde::Path) -> String {
+    // `__iter` yields the components from the innermost one outwards.
+    path.__iter()
+        .rev()
+        .map(|(_, segment)| segment.as_str())
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| format!("/{}", escape(segment)))
+        .collect()
+}
+
+/// Escapes a JSON Pointer reference token.
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, routing::post, Router};
+    use http_body_util::BodyExt;
+    use serde::Deserialize;
+    use serde_json::Value;
+    use serial_test::serial;
+    use tower::ServiceExt;
+
+    async fn post_json(app: Router, body: &str) -> (StatusCode, Value) {
+        let request = Request::post("/")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    #[cfg(feature = "validator")]
+    mod validator_tests {
+        use super::*;
+        use serde_json::json;
+        use validator::Validate;
+
+        #[derive(Deserialize, Validate)]
+        struct Address {
+            #[validate(length(min = 1, message = "City is required"))]
+            city: String,
+        }
+
+        #[derive(Deserialize, Validate)]
+        struct User {
+            #[validate(email)]
+            email: String,
+            #[validate(range(min = 18))]
+            age: u32,
+            #[validate(nested)]
+            address: Address,
+        }
+
+        fn app() -> Router {
+            Router::new().route(
+                "/",
+                post(|Valid(Json(user)): Valid<Json<User>>| async move { user.email }),
+            )
+        }
+
+        #[tokio::test]
+        async fn test_valid_accepts_valid_body() {
+            let body = r#"{"email": "a@example.com", "age": 30, "address": {"city": "Oslo"}}"#;
+            let (status, _) = post_json(app(), body).await;
+
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        #[tokio::test]
+        #[serial]
+        async fn test_valid_rejects_with_field_errors() {
+            let body = r#"{"email": "invalid", "age": 12, "address": {"city": ""}}"#;
+            let (status, body) = post_json(app(), body).await;
+
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(
+                body["errors"],
+                json!([
+                    {"pointer": "/address/city", "code": "length", "message": "City is required", "params": {"min": 1}},
+                    {"pointer": "/age", "code": "range", "message": "Invalid range", "params": {"min": 18}},
+                    {"pointer": "/email", "code": "email", "message": "Invalid email"},
+                ])
+            );
+        }
+
+        #[tokio::test]
+        async fn test_valid_rejects_malformed_json() {
+            let (status, body) = post_json(app(), r#"{"email": "#).await;
+
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body["title"], "Bad Request");
+        }
+    }
+
+    #[cfg(feature = "garde")]
+    mod garde_tests {
+        use super::*;
+        use garde::Validate;
+
+        #[derive(Deserialize, Validate)]
+        struct Item {
+            #[garde(length(min = 1))]
+            name: String,
+        }
+
+        #[derive(Deserialize, Validate)]
+        struct Order {
+            #[garde(range(min = 1))]
+            quantity: u32,
+            #[garde(dive)]
+            items: Vec<Item>,
+        }
+
+        #[tokio::test]
+        #[serial]
+        async fn test_garde_rejects_with_field_errors() {
+            let app =
+                Router::new().route(
+                    "/",
+                    post(|Garde(Json(order)): Garde<Json<Order>>| async move {
+                        order.quantity.to_string()
+                    }),
+                );
+            let (status, body) =
+                post_json(app, r#"{"quantity": 0, "items": [{"name": ""}]}"#).await;
+
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            let pointers = body["errors"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|error| error["pointer"].as_str().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(pointers, ["/items/0/name", "/quantity"]);
+        }
+
+        #[test]
+        fn test_garde_pointer() {
+            use garde::Path;
+
+            let pointer = |path: Path| garde_pointer(&path);
+            assert_eq!(pointer(Path::new("address").join("city")), "/address/city");
+            assert_eq!(
+                pointer(Path::new("items").join(0usize).join("name")),
+                "/items/0/name"
+            );
+            assert_eq!(pointer(Path::new(1usize)), "/1");
+            assert_eq!(
+                pointer(Path::new("labels").join("app.kubernetes.io/name")),
+                "/labels/app.kubernetes.io~1name"
+            );
+            assert_eq!(pointer(Path::new("limits").join("~max")), "/limits/~0max");
+        }
+
+        #[tokio::test]
+        #[serial]
+        async fn test_garde_map_key_with_dot_is_one_segment() {
+            #[derive(Deserialize, Validate)]
+            struct Labels {
+                #[garde(dive)]
+                labels: std::collections::HashMap<String, Item>,
+            }
+
+            let app = Router::new().route(
+                "/",
+                post(|Garde(Json(labels)): Garde<Json<Labels>>| async move {
+                    labels.labels.len().to_string()
+                }),
+            );
+            let (status, body) = post_json(app, r#"{"labels": {"a.b/c": {"name": ""}}}"#).await;
+
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(body["errors"][0]["pointer"], "/labels/a.b~1c/name");
+        }
+    }
+}
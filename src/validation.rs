@@ -0,0 +1,373 @@
+//! Field-level validation errors.
+//!
+//! A [`FieldError`] says which part of the request is invalid and why. Several of them
+//! can be attached to one `ApiError`, and [`FieldErrors`] collects them while a request
+//! is being validated.
+
+use crate::{ApiError, ApiErrorBuilder, ApiResult};
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt::Display;
+
+/// A single invalid field of a request.
+///
+/// The pointer is either a JSON Pointer ([RFC 6901](https://www.rfc-editor.org/rfc/rfc6901))
+/// into the request body, such as `"/address/city"`, or the name of a query parameter,
+/// such as `"page"`.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_anyhow::FieldError;
+/// use serde_json::json;
+///
+/// let error = FieldError::new("/age", "Age must be at least 18")
+///     .code("range")
+///     .param("min", 18);
+///
+/// assert_eq!(error.pointer, "/age");
+/// assert_eq!(error.code.as_deref(), Some("range"));
+/// assert_eq!(error.params.unwrap()["min"], json!(18));
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// A JSON Pointer into the request body, or a query parameter name
+    #[serde(alias = "name")]
+    pub pointer: String,
+    /// A machine-readable code for the failed rule, such as `"email"` or `"range"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// A human-readable explanation of what is wrong with the field
+    #[serde(alias = "reason")]
+    pub message: String,
+    /// The parameters of the failed rule, such as `{"min": 18}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Map<String, Value>>,
+}
+
+impl FieldError {
+    /// Creates a field error with the given pointer and message.
+    pub fn new(pointer: impl Into<String>, message: impl Into<String>) -> Self {
+        FieldError {
+            pointer: pointer.into(),
+            code: None,
+            message: message.into(),
+            params: None,
+        }
+    }
+
+    /// Sets the machine-readable code of the failed rule.
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Adds a parameter of the failed rule.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.params
+            .get_or_insert_with(Map::new)
+            .insert(name.into(), value.into());
+        self
+    }
+
+    /// Splits the pointer into unescaped segments. A query parameter name is a single
+    /// segment.
+    pub(crate) fn segments(&self) -> Vec<String> {
+        match self.pointer.strip_prefix('/') {
+            Some(pointer) => pointer
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+                .collect(),
+            None if self.pointer.is_empty() => Vec::new(),
+            None => vec![self.pointer.clone()],
+        }
+    }
+}
+
+impl ApiErrorBuilder {
+    /// Adds a field error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use axum_anyhow::{ApiError, FieldError};
+    ///
+    /// let error = ApiError::builder()
+    ///     .status(StatusCode::UNPROCESSABLE_ENTITY)
+    ///     .title("Validation Failed")
+    ///     .field_error(FieldError::new("/email", "Email must contain @"))
+    ///     .field_error(FieldError::new("/age", "Age must be positive"))
+    ///     .build();
+    ///
+    /// assert_eq!(error.field_errors().len(), 2);
+    /// ```
+    pub fn field_error(mut self, error: FieldError) -> Self {
+        self.extras_mut().field_errors.push(error);
+        self
+    }
+
+    /// Adds several field errors.
+    pub fn field_errors(mut self, errors: impl IntoIterator<Item = FieldError>) -> Self {
+        self.extras_mut().field_errors.extend(errors);
+        self
+    }
+}
+
+/// Collects field errors while validating a request.
+///
+/// Each check records a failure instead of returning early, so the client learns about
+/// every invalid field at once. [`finish`](FieldErrors::finish) then fails with a
+/// `422 Unprocessable Entity` if anything was recorded.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum_anyhow::{ApiResult, FieldErrors};
+///
+/// fn validate(email: &str, age: &str) -> ApiResult<(String, u32)> {
+///     let mut errors = FieldErrors::new();
+///
+///     errors.ensure(email.contains('@'), "/email", "Email must contain @");
+///     let age = errors.check(age.parse::<u32>(), "/age");
+///
+///     errors.finish()?;
+///     Ok((email.to_string(), age.unwrap()))
+/// }
+///
+/// let error = validate("invalid", "-1").unwrap_err();
+/// assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+/// assert_eq!(error.field_errors().len(), 2);
+/// assert_eq!(error.field_errors()[1].message, "invalid digit found in string");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FieldErrors {
+    errors: Vec<FieldError>,
+}
+
+impl FieldErrors {
+    /// Creates an empty collector.
+    pub fn new() -> Self {
+        FieldErrors::default()
+    }
+
+    /// Records a field error.
+    pub fn push(&mut self, error: FieldError) {
+        self.errors.push(error);
+    }
+
+    /// Records a field error with the given message if `condition` is false.
+    pub fn ensure(
+        &mut self,
+        condition: bool,
+        pointer: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        if !condition {
+            self.push(FieldError::new(pointer, message));
+        }
+    }
+
+    /// Returns the value of an `Ok` result, or records its error message as a field
+    /// error and returns `None`.
+    pub fn check<T, E: Display>(
+        &mut self,
+        result: Result<T, E>,
+        pointer: impl Into<String>,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.push(FieldError::new(pointer, err.to_string()));
+                None
+            }
+        }
+    }
+
+    /// Returns the value of a `Some` option, or records a field error with the given
+    /// message and returns `None`.
+    pub fn require<T>(
+        &mut self,
+        option: Option<T>,
+        pointer: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Option<T> {
+        if option.is_none() {
+            self.push(FieldError::new(pointer, message));
+        }
+        option
+    }
+
+    /// Returns `true` if no field errors have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the number of recorded field errors.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Succeeds if no field errors have been recorded, and otherwise fails with a
+    /// `422 Unprocessable Entity` error carrying all of them.
+    pub fn finish(self) -> ApiResult<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        Err(self.into())
+    }
+}
+
+/// Converts collected field errors into a `422 Unprocessable Entity` error.
+impl From<FieldErrors> for ApiError {
+    fn from(errors: FieldErrors) -> Self {
+        ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("Validation Failed")
+            .field_errors(errors.errors)
+            .build()
+    }
+}
+
+impl Extend<FieldError> for FieldErrors {
+    fn extend<I: IntoIterator<Item = FieldError>>(&mut self, iter: I) {
+        self.errors.extend(iter);
+    }
+}
+
+impl FromIterator<FieldError> for FieldErrors {
+    fn from_iter<I: IntoIterator<Item = FieldError>>(iter: I) -> Self {
+        FieldErrors {
+            errors: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for FieldErrors {
+    type Item = FieldError;
+    type IntoIter = std::vec::IntoIter<FieldError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{field_error_format, set_field_error_format, FieldErrorFormat};
+    use axum::response::IntoResponse;
+    use http_body_util::BodyExt;
+    use serde_json::json;
+    use serial_test::serial;
+
+    /// Sets the field error format, restoring the previous one when dropped, even if the
+    /// test fails.
+    struct FieldErrorFormatGuard(FieldErrorFormat);
+
+    impl FieldErrorFormatGuard {
+        fn set(format: FieldErrorFormat) -> Self {
+            let previous = field_error_format();
+            set_field_error_format(format);
+            Self(previous)
+        }
+    }
+
+    impl Drop for FieldErrorFormatGuard {
+        fn drop(&mut self) {
+            set_field_error_format(self.0);
+        }
+    }
+
+    async fn body(error: ApiError) -> Value {
+        let bytes = error
+            .into_response()
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_finish_without_errors() {
+        let mut errors = FieldErrors::new();
+        errors.ensure(true, "/email", "Email must contain @");
+        assert_eq!(errors.require(Some(1), "/age", "Age is required"), Some(1));
+
+        assert!(errors.finish().is_ok());
+    }
+
+    #[test]
+    fn test_finish_collects_every_failure() {
+        let mut errors = FieldErrors::new();
+        errors.ensure(false, "/email", "Email must contain @");
+        errors.require(None::<u32>, "/age", "Age is required");
+        errors.check("x".parse::<u32>(), "page");
+
+        let error = errors.finish().unwrap_err();
+
+        assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.title(), "Validation Failed");
+        let pointers: Vec<_> = error.field_errors().iter().map(|e| &e.pointer).collect();
+        assert_eq!(pointers, ["/email", "/age", "page"]);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_field_errors_serialized_as_errors() {
+        let error = ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("Validation Failed")
+            .field_error(
+                FieldError::new("/age", "Age must be at least 18")
+                    .code("range")
+                    .param("min", 18),
+            )
+            .build();
+
+        assert_eq!(
+            body(error).await["errors"],
+            json!([{
+                "pointer": "/age",
+                "code": "range",
+                "message": "Age must be at least 18",
+                "params": {"min": 18},
+            }])
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_field_errors_serialized_as_invalid_params() {
+        let _format = FieldErrorFormatGuard::set(FieldErrorFormat::InvalidParams);
+        let error = ApiError::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .title("Invalid Request")
+            .field_error(FieldError::new("page", "Page must be a number"))
+            .build();
+        let body = body(error).await;
+
+        assert!(body.get("errors").is_none());
+        assert_eq!(
+            body["invalid-params"],
+            json!([{"name": "page", "reason": "Page must be a number"}])
+        );
+    }
+
+    #[test]
+    fn test_field_error_deserializes_both_shapes() {
+        let errors: Vec<FieldError> = serde_json::from_value(json!([
+            {"pointer": "/email", "message": "Invalid email"},
+            {"name": "page", "reason": "Not a number"},
+        ]))
+        .unwrap();
+
+        assert_eq!(errors[0], FieldError::new("/email", "Invalid email"));
+        assert_eq!(errors[1], FieldError::new("page", "Not a number"));
+    }
+}
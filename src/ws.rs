@@ -0,0 +1,213 @@
+//! Reporting errors to WebSocket clients.
+//!
+//! A WebSocket session has no status code to fail with once it is upgraded, so an
+//! `ApiError` is reported with the close frame that ends the session, optionally
+//! preceded by its JSON body as a text message.
+
+use crate::{format::error_format, ApiError, ErrorFormat};
+use axum::{
+    extract::ws::{close_code, CloseCode, CloseFrame, Message},
+    http::StatusCode,
+};
+
+/// The maximum length of a close frame reason in bytes.
+///
+/// A control frame payload is at most 125 bytes, two of which hold the close code.
+const MAX_REASON_LEN: usize = 123;
+
+impl ApiError {
+    /// Returns the WebSocket close code for the status code of this error.
+    ///
+    /// | Status code             | Close code                 |
+    /// | ----------------------- | -------------------------- |
+    /// | 4xx                     | 4000 + status, e.g. `4401` |
+    /// | 503 Service Unavailable | `1013` Try Again Later     |
+    /// | Any other status        | `1011` Internal Error      |
+    ///
+    /// Client errors use the private range 4000–4999, so that a client can tell a
+    /// `4401` Unauthorized apart from a `4403` Forbidden.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum_anyhow::{forbidden, internal_error, service_unavailable};
+    ///
+    /// assert_eq!(forbidden("Forbidden", "Not your room").close_code(), 4403);
+    /// assert_eq!(internal_error("Internal Error", "Oops").close_code(), 1011);
+    /// assert_eq!(service_unavailable("Unavailable", "Restarting").close_code(), 1013);
+    /// ```
+    pub fn close_code(&self) -> CloseCode {
+        let status = self.status();
+        if status.is_client_error() {
+            4000 + status.as_u16()
+        } else if status == StatusCode::SERVICE_UNAVAILABLE {
+            close_code::AGAIN
+        } else {
+            close_code::ERROR
+        }
+    }
+
+    /// Converts this error into a close frame.
+    ///
+    /// The code comes from [`close_code`](ApiError::close_code) and the reason is the
+    /// title, truncated to the 123 bytes that fit in a close frame.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::extract::ws::Message;
+    /// use axum_anyhow::unauthorized;
+    ///
+    /// let frame = unauthorized("Session Expired", "Log in again").into_close_frame();
+    /// assert_eq!(frame.code, 4401);
+    /// assert_eq!(frame.reason.as_str(), "Session Expired");
+    ///
+    /// let message = Message::Close(Some(frame));
+    /// ```
+    pub fn into_close_frame(self) -> CloseFrame {
+        self.close_frame()
+    }
+
+    /// Converts this error into the messages that report it to a WebSocket client: a
+    /// text message with the JSON body of the error, followed by a close frame.
+    ///
+    /// The body is rendered in the current [`ErrorFormat`], so the client gets the detail
+    /// and metadata that do not fit in the close frame. The format of an
+    /// [`ErrorFormatLayer`](crate::ErrorFormatLayer) only applies while the handler runs,
+    /// not in the `on_upgrade` callback, so routes with their own format should use
+    /// [`into_ws_messages_as`](ApiError::into_ws_messages_as) instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::extract::ws::WebSocket;
+    /// use axum_anyhow::ApiError;
+    ///
+    /// async fn fail(mut socket: WebSocket, error: ApiError) {
+    ///     for message in error.into_ws_messages() {
+    ///         if socket.send(message).await.is_err() {
+    ///             break;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn into_ws_messages(self) -> [Message; 2] {
+        self.into_ws_messages_as(error_format())
+    }
+
+    /// Converts this error into the messages that report it to a WebSocket client, with
+    /// the body rendered in the given format.
+    ///
+    /// Read the format with [`error_format`](crate::error_format) in the handler, where
+    /// the format of an [`ErrorFormatLayer`](crate::ErrorFormatLayer) is still set, and
+    /// pass it to the session.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::{extract::ws::WebSocketUpgrade, response::Response};
+    /// use axum_anyhow::{error_format, forbidden};
+    ///
+    /// async fn handler(ws: WebSocketUpgrade) -> Response {
+    ///     let format = error_format();
+    ///     ws.on_upgrade(move |mut socket| async move {
+    ///         let error = forbidden("Forbidden", "The game is private");
+    ///         for message in error.into_ws_messages_as(format) {
+    ///             if socket.send(message).await.is_err() {
+    ///                 break;
+    ///             }
+    ///         }
+    ///     })
+    /// }
+    /// ```
+    pub fn into_ws_messages_as(self, format: ErrorFormat) -> [Message; 2] {
+        let frame = self.close_frame();
+        let body = self.into_json(format).to_string();
+        [Message::Text(body.into()), Message::Close(Some(frame))]
+    }
+
+    fn close_frame(&self) -> CloseFrame {
+        CloseFrame {
+            code: self.close_code(),
+            reason: truncate(self.title(), MAX_REASON_LEN).into(),
+        }
+    }
+}
+
+/// Converts an `ApiError` into a close frame with [`ApiError::into_close_frame`].
+impl From<ApiError> for CloseFrame {
+    fn from(error: ApiError) -> Self {
+        error.into_close_frame()
+    }
+}
+
+/// Truncates a string to at most `max` bytes without splitting a character.
+fn truncate(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bad_request;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_close_codes() {
+        let code = |status| ApiError::builder().status(status).build().close_code();
+
+        assert_eq!(code(StatusCode::UNAUTHORIZED), 4401);
+        assert_eq!(code(StatusCode::FORBIDDEN), 4403);
+        assert_eq!(code(StatusCode::TOO_MANY_REQUESTS), 4429);
+        assert_eq!(code(StatusCode::INTERNAL_SERVER_ERROR), 1011);
+        assert_eq!(code(StatusCode::BAD_GATEWAY), 1011);
+        assert_eq!(code(StatusCode::SERVICE_UNAVAILABLE), 1013);
+    }
+
+    #[test]
+    fn test_close_frame_truncates_reason_on_char_boundary() {
+        let title = "é".repeat(100);
+        let frame = CloseFrame::from(bad_request(&title, "Too long"));
+
+        assert_eq!(frame.code, 4400);
+        assert_eq!(frame.reason.len(), 122);
+        assert_eq!(frame.reason.as_str(), "é".repeat(61));
+    }
+
+    #[test]
+    fn test_ws_messages_send_body_before_close() {
+        let [text, close] = bad_request("Invalid Move", "The square is taken").into_ws_messages();
+
+        let Message::Text(text) = text else {
+            panic!("expected a text message");
+        };
+        let body: Value = serde_json::from_str(text.as_str()).unwrap();
+        assert_eq!(body["detail"], json!("The square is taken"));
+        assert_eq!(
+            close,
+            Message::Close(Some(CloseFrame {
+                code: 4400,
+                reason: "Invalid Move".into(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_ws_messages_in_given_format() {
+        let [text, _] = bad_request("Invalid Move", "The square is taken")
+            .into_ws_messages_as(ErrorFormat::Twirp);
+
+        let Message::Text(text) = text else {
+            panic!("expected a text message");
+        };
+        let body: Value = serde_json::from_str(text.as_str()).unwrap();
+        assert_eq!(body["code"], json!("invalid_argument"));
+    }
+}
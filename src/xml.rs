@@ -0,0 +1,309 @@
+//! XML error bodies.
+//!
+//! Errors are rendered either as an RFC 9457 problem document, sent as
+//! `application/problem+xml`:
+//!
+//! ```xml
+//! <?xml version="1.0" encoding="UTF-8"?>
+//! <problem xmlns="urn:ietf:rfc:7807">
+//!   <status>404</status>
+//!   <title>Not Found</title>
+//!   <detail>No such key</detail>
+//!   <meta><bucket>photos</bucket></meta>
+//! </problem>
+//! ```
+//!
+//! or in the shape of S3 error responses, sent as `application/xml`:
+//!
+//! ```xml
+//! <?xml version="1.0" encoding="UTF-8"?>
+//! <Error>
+//!   <Code>NoSuchKey</Code>
+//!   <Message>Not Found: No such key</Message>
+//!   <Resource>/photos/cat.jpg</Resource>
+//!   <RequestId>4442587FB7D0A2F9</RequestId>
+//!   <bucket>photos</bucket>
+//! </Error>
+//! ```
+//!
+//! Objects in the metadata become nested elements and arrays become `<i>` elements, as
+//! in [Appendix B](https://www.rfc-editor.org/rfc/rfc9457.html#appendix-B) of RFC 9457.
+//! The documents above are indented for readability; the rendered bodies are not.
+
+use crate::{naming::pascal_case, ApiError, ErrorFormat};
+use axum::{
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+
+const PROBLEM_XML_CONTENT_TYPE: &str = "application/problem+xml";
+const XML_CONTENT_TYPE: &str = "application/xml";
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+/// Renders an `ApiError` as an `application/problem+xml` response.
+pub(crate) fn into_problem_response(error: ApiError) -> Response {
+    let status = error.status();
+    let mut response = (status, problem_xml(error)).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(PROBLEM_XML_CONTENT_TYPE),
+    );
+    response
+}
+
+/// Renders an `ApiError` as an S3-style `application/xml` response.
+pub(crate) fn into_s3_response(error: ApiError) -> Response {
+    let status = error.status();
+    let mut response = (status, s3_xml(&error)).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(XML_CONTENT_TYPE),
+    );
+    response
+}
+
+/// Builds the problem document from the members of the default JSON body.
+fn problem_xml(error: ApiError) -> String {
+    let Value::Object(mut members) = error.into_json(ErrorFormat::Problem) else {
+        unreachable!("the default body is always an object");
+    };
+
+    let mut xml = format!(r#"{XML_DECLARATION}<problem xmlns="urn:ietf:rfc:7807">"#);
+    for name in ["status", "title", "detail"] {
+        if let Some(value) = members.remove(name) {
+            element(&mut xml, name, &value);
+        }
+    }
+    for (name, value) in &members {
+        element(&mut xml, name, value);
+    }
+    xml.push_str("</problem>");
+    xml
+}
+
+/// Builds the S3 error document.
+///
+/// The `resource` and `request_id` members of the metadata become the `Resource` and
+/// `RequestId` elements, and the other members are added after them.
+fn s3_xml(error: &ApiError) -> String {
+    let code = error.code().map_or_else(|| s3_code(error), str::to_string);
+    let mut meta = match error.meta() {
+        Some(Value::Object(meta)) => meta.clone(),
+        Some(Value::Null) | None => Default::default(),
+        Some(meta) => [("meta".to_string(), meta.clone())].into_iter().collect(),
+    };
+
+    let mut xml = format!("{XML_DECLARATION}<Error>");
+    element(&mut xml, "Code", &Value::String(code));
+    element(&mut xml, "Message", &Value::String(error.message()));
+    for (key, name) in [("resource", "Resource"), ("request_id", "RequestId")] {
+        if let Some(value) = meta.remove(key) {
+            element(&mut xml, name, &value);
+        }
+    }
+    for (name, value) in &meta {
+        element(&mut xml, name, value);
+    }
+    xml.push_str("</Error>");
+    xml
+}
+
+/// Converts the reason phrase of the status code to an S3-style code, such as
+/// `"NotFound"`.
+fn s3_code(error: &ApiError) -> String {
+    pascal_case(error.status().canonical_reason().unwrap_or("Unknown"))
+}
+
+/// Writes a JSON value as an element.
+fn element(xml: &mut String, name: &str, value: &Value) {
+    let name = element_name(name);
+    if value.is_null() {
+        xml.push_str(&format!("<{name}/>"));
+        return;
+    }
+
+    xml.push_str(&format!("<{name}>"));
+    match value {
+        Value::Object(members) => {
+            for (name, value) in members {
+                element(xml, name, value);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                element(xml, "i", item);
+            }
+        }
+        Value::String(text) => escape(xml, text),
+        value => xml.push_str(&value.to_string()),
+    }
+    xml.push_str(&format!("</{name}>"));
+}
+
+/// Replaces the characters that are not allowed in an XML element name with `_`.
+fn element_name(name: &str) -> String {
+    let mut element = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if !element
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+    {
+        element.insert(0, '_');
+    }
+    element
+}
+
+/// Escapes text content, dropping the control characters that XML 1.0 does not allow.
+fn escape(xml: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            '\t' | '\n' | '\r' => xml.push(c),
+            '\0'..='\x1f' => {}
+            c => xml.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldError;
+    use axum::http::StatusCode;
+    use http_body_util::BodyExt;
+    use serde_json::json;
+    use serial_test::serial;
+
+    async fn body(error: ApiError, format: ErrorFormat) -> (String, String) {
+        let response = error.into_response_as(format);
+        let content_type = response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (content_type, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_problem_xml() {
+        let error = ApiError::builder()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .title("Invalid <Order>")
+            .detail("Quantity & price are required")
+            .meta(json!({"order": {"id": 7, "tags": ["rush", null]}}))
+            .field_error(FieldError::new("/quantity", "Required"))
+            .build();
+
+        let (content_type, xml) = body(error, ErrorFormat::ProblemXml).await;
+
+        assert_eq!(content_type, "application/problem+xml");
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<problem xmlns="urn:ietf:rfc:7807">"#,
+                "<status>422</status>",
+                "<title>Invalid &lt;Order&gt;</title>",
+                "<detail>Quantity &amp; price are required</detail>",
+                "<errors><i><message>Required</message><pointer>/quantity</pointer></i></errors>",
+                "<meta><order><id>7</id><tags><i>rush</i><i/></tags></order></meta>",
+                "</problem>",
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_s3_xml() {
+        let error = ApiError::builder()
+            .status(StatusCode::NOT_FOUND)
+            .title("Not Found")
+            .detail("No such key")
+            .code("NoSuchKey")
+            .meta(json!({
+                "resource": "/photos/cat.jpg",
+                "request_id": "4442587FB7D0A2F9",
+                "bucket": "photos",
+                "2fa": true,
+            }))
+            .build();
+
+        let (content_type, xml) = body(error, ErrorFormat::S3).await;
+
+        assert_eq!(content_type, "application/xml");
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                "<Error>",
+                "<Code>NoSuchKey</Code>",
+                "<Message>Not Found: No such key</Message>",
+                "<Resource>/photos/cat.jpg</Resource>",
+                "<RequestId>4442587FB7D0A2F9</RequestId>",
+                "<_2fa>true</_2fa>",
+                "<bucket>photos</bucket>",
+                "</Error>",
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_s3_code_from_status() {
+        let error = ApiError::builder()
+            .status(StatusCode::FORBIDDEN)
+            .title("Access Denied")
+            .build();
+
+        let (_, xml) = body(error, ErrorFormat::S3).await;
+
+        assert!(xml.contains("<Code>Forbidden</Code><Message>Access Denied</Message></Error>"));
+        assert_eq!(s3_code(&ApiError::builder().build()), "InternalServerError");
+    }
+
+    #[tokio::test]
+    async fn test_xml_chosen_by_accept_header() {
+        use crate::{not_found, ApiResult, ErrorFormatLayer};
+        use axum::{body::Body, extract::Request, routing::get, Router};
+        use tower::ServiceExt;
+
+        async fn handler() -> ApiResult<()> {
+            Err(not_found("Not Found", "No such key"))
+        }
+
+        let app = Router::new().route("/", get(handler)).layer(
+            ErrorFormatLayer::new(ErrorFormat::Problem)
+                .accept("application/problem+xml", ErrorFormat::ProblemXml)
+                .accept("application/xml", ErrorFormat::S3),
+        );
+        let content_type = |accept: &'static str| {
+            let app = app.clone();
+            async move {
+                let request = Request::get("/")
+                    .header(header::ACCEPT, accept)
+                    .body(Body::empty())
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                response.headers()[header::CONTENT_TYPE].clone()
+            }
+        };
+
+        assert_eq!(content_type("application/xml").await, "application/xml");
+        assert_eq!(
+            content_type("application/problem+xml, application/xml;q=0.9").await,
+            "application/problem+xml"
+        );
+        assert_eq!(content_type("application/json").await, "application/json");
+    }
+}